@@ -9,8 +9,8 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
     let fn_name = methods.iter().map(|method| &method.name).collect::<Vec<_>>();
 
     // This is a nested vector of all the arguments for each of the functions in `fn_name`. It does
-    // not include the `self` or `caller: T::AccountId` parameter, which we always assume are the
-    // first two parameters to these calls.
+    // not include the `self`, `runtime: &mut T` or `caller: T::AccountId` parameters, which we
+    // always assume are the first parameters to these calls.
     let args_name = methods
         .iter()
         .map(|method| method.args.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>())
@@ -23,6 +23,20 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
         .map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
         .collect::<Vec<_>>();
 
+    // This is the runtime argument passed to each of the functions in `fn_name`, for those which
+    // take one.
+    let runtime_arg = methods
+        .iter()
+        .map(|method| if method.with_runtime { quote!(runtime,) } else { quote!() })
+        .collect::<Vec<_>>();
+
+    // This is the weight expression declared for each of the functions in `fn_name`.
+    let weight = methods.iter().map(|method| &method.weight).collect::<Vec<_>>();
+
+    // This is the index of each of the functions in `fn_name`, declared with `#[call_index(...)]`
+    // and used to encode the `Call`.
+    let call_index = methods.iter().map(|method| method.call_index).collect::<Vec<_>>();
+
     // This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
     // and the `Dispatch` trait logic to route a `caller` to access those functions.
     let dispatch_impl = quote! {
@@ -30,24 +44,46 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone)]
 		pub enum Call<T: Config> {
 			#(
 				#fn_name { #( #args_name: #args_type),* },
 			)*
 		}
 
+		impl<T: Config> Call<T> {
+			// The weight of the call, as declared with `#[weight(...)]` on its function. The weight
+			// expression can refer to the arguments of the call, by reference.
+			#[allow(unused_variables)]
+			pub fn weight(&self) -> crate::support::Weight {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => #weight,
+					)*
+				}
+			}
+		}
+
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
 		// appropriate function call with all arguments, including the `caller`.
-		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
-			type Caller = T::AccountId;
-			type Call = Call<T>;
-
-			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
+		//
+		// The `runtime` is the rest of the runtime, without this pallet, and is only passed to the
+		// calls which take a `runtime: &mut T` argument. Every pallet declares an `Error` enum next
+		// to its `Config`.
+		impl<T: Config> #pallet_struct<T> {
+			#[allow(unused_variables)]
+			pub fn dispatch(
+				&mut self,
+				runtime: &mut T,
+				caller: T::AccountId,
+				call: Call<T>,
+			) -> crate::support::DispatchResult<Error> {
 				match call {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
 							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
+								#runtime_arg
+								// Note that we assume the `caller` comes before the call arguments.
 								caller,
 								#( #args_name ),*
 							)?;
@@ -57,8 +93,39 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 				Ok(())
 			}
 		}
+
+		// A call is encoded as the index of the function, followed by its arguments.
+		impl<T: Config> crate::codec::Encode for Call<T> {
+			fn encode_to(&self, dest: &mut Vec<u8>) {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => {
+							dest.push(#call_index);
+							#( crate::codec::Encode::encode_to(#args_name, dest); )*
+						},
+					)*
+				}
+			}
+		}
+
+		// A call is decoded from the index of the function, followed by its arguments.
+		impl<T: Config> crate::codec::Decode for Call<T>
+		where
+			#( #( #args_type: crate::codec::Decode, )* )*
+		{
+			fn decode_from(input: &mut &[u8]) -> Result<Self, crate::codec::Error> {
+				match <u8 as crate::codec::Decode>::decode_from(input)? {
+					#(
+						#call_index => Ok(Call::#fn_name {
+							#( #args_name: crate::codec::Decode::decode_from(input)?, )*
+						}),
+					)*
+					_ => Err(crate::codec::Error::Invalid("unknown call")),
+				}
+			}
+		}
 	};
 
     // Return the generated code.
-    dispatch_impl.into()
+    dispatch_impl
 }
\ No newline at end of file
@@ -25,6 +25,14 @@ pub struct CallVariantDef {
     pub name: syn::Ident,
     /// Information on args of the function: `(name, type)`.
     pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+    /// The weight of the function, declared with `#[weight(...)]`.
+    pub weight: syn::Expr,
+    /// The index of the function, declared with `#[call_index(...)]`, which identifies the call in
+    /// its encoding.
+    pub call_index: u8,
+    /// Whether the function takes a `runtime: &mut T` argument, giving it access to the rest of
+    /// the runtime.
+    pub with_runtime: bool,
 }
 
 impl CallDef {
@@ -44,7 +52,7 @@ impl CallDef {
         };
 
         // Here is where we will store all the callable functions.
-        let mut methods = vec![];
+        let mut methods: Vec<CallVariantDef> = vec![];
         for item in item_impl.items {
             if let syn::ImplItem::Fn(method) = item {
                 // Here is where we will store all the args for each callable functions.
@@ -59,23 +67,47 @@ impl CallDef {
                     },
                 }
 
-                // The second argument should be the `caller: T::AccountId` argument.
-                match method.sig.inputs.iter().skip(1).next() {
+                // The second argument may be `runtime: &mut T`, for calls which need to access the
+                // rest of the runtime.
+                let with_runtime = match method.sig.inputs.iter().nth(1) {
+                    Some(syn::FnArg::Typed(arg)) => check_runtime_arg(arg)?,
+                    _ => false,
+                };
+                let skip = if with_runtime { 2 } else { 1 };
+
+                // The next argument should be the `caller: T::AccountId` argument.
+                match method.sig.inputs.iter().nth(skip) {
                     Some(syn::FnArg::Typed(arg)) => {
                         // Here we specifically check that this argument is as we expect for
                         // `caller: T::AccountId`.
                         check_caller_arg(arg)?;
                     },
                     _ => {
-                        let msg = "Invalid call, second argument should be `caller: T::AccountId`";
+                        let msg = "Invalid call, expected a `caller: T::AccountId` argument";
                         return Err(syn::Error::new(method.sig.span(), msg))
                     },
                 }
 
                 let fn_name = method.sig.ident.clone();
 
-                // Parsing the rest of the args. Skipping 2 for `self` and `caller`.
-                for arg in method.sig.inputs.iter().skip(2) {
+                // Every callable function must declare its weight.
+                let weight = parse_weight(&method)?;
+
+                // Every callable function must declare its index, which can't be used twice.
+                let call_index = parse_call_index(&method)?;
+                let index = call_index.base10_parse::<u8>()?;
+                if let Some(other) =
+                    methods.iter().find(|other| other.call_index == index)
+                {
+                    let msg = format!(
+                        "Invalid call, the call index {} is already used by `{}`",
+                        index, other.name
+                    );
+                    return Err(syn::Error::new(call_index.span(), msg))
+                }
+
+                // Parsing the rest of the args. Skipping `self`, `runtime` and `caller`.
+                for arg in method.sig.inputs.iter().skip(skip + 1) {
                     // All arguments should be typed.
                     let arg = if let syn::FnArg::Typed(arg) = arg {
                         arg
@@ -96,7 +128,13 @@ impl CallDef {
                 }
 
                 // Store all the function name and the arg data for the function.
-                methods.push(CallVariantDef { name: fn_name, args });
+                methods.push(CallVariantDef {
+                    name: fn_name,
+                    args,
+                    weight,
+                    call_index: index,
+                    with_runtime,
+                });
             }
         }
 
@@ -105,6 +143,85 @@ impl CallDef {
     }
 }
 
+/// Extract the expression of the `#[weight(...)]` attribute of a callable function.
+fn parse_weight(method: &syn::ImplItemFn) -> syn::Result<syn::Expr> {
+    let mut weights = method.attrs.iter().filter(|attr| attr.path().is_ident("weight"));
+
+    let weight = match weights.next() {
+        Some(attr) => attr.parse_args::<syn::Expr>()?,
+        None => {
+            let msg = "Invalid call, expected a `#[weight(...)]` attribute";
+            return Err(syn::Error::new(method.sig.span(), msg))
+        },
+    };
+
+    if let Some(attr) = weights.next() {
+        let msg = "Invalid call, only one `#[weight(...)]` attribute is allowed";
+        return Err(syn::Error::new(attr.span(), msg))
+    }
+
+    Ok(weight)
+}
+
+/// Extract the index of the `#[call_index(...)]` attribute of a callable function.
+fn parse_call_index(method: &syn::ImplItemFn) -> syn::Result<syn::LitInt> {
+    let mut indices = method.attrs.iter().filter(|attr| attr.path().is_ident("call_index"));
+
+    let index = match indices.next() {
+        Some(attr) => attr.parse_args::<syn::LitInt>()?,
+        None => {
+            let msg = "Invalid call, expected a `#[call_index(...)]` attribute";
+            return Err(syn::Error::new(method.sig.span(), msg))
+        },
+    };
+
+    if let Some(attr) = indices.next() {
+        let msg = "Invalid call, only one `#[call_index(...)]` attribute is allowed";
+        return Err(syn::Error::new(attr.span(), msg))
+    }
+
+    Ok(index)
+}
+
+/// Remove the attributes which only have a meaning for this macro, like `#[weight(...)]` and
+/// `#[call_index(...)]`, since they are not valid Rust attributes.
+pub fn strip_call_attributes(item: &mut syn::Item) {
+    if let syn::Item::Impl(item_impl) = item {
+        for item in item_impl.items.iter_mut() {
+            if let syn::ImplItem::Fn(method) = item {
+                method.attrs.retain(|attr| {
+                    !attr.path().is_ident("weight") && !attr.path().is_ident("call_index")
+                });
+            }
+        }
+    }
+}
+
+/// Check if an arg is the optional `runtime: &mut T`. Any other name means the arg is not the
+/// runtime, but the runtime arg must have exactly this type.
+pub fn check_runtime_arg(arg: &syn::PatType) -> syn::Result<bool> {
+    // This checks the arg name is `runtime` or `_runtime`.
+    match &*arg.pat {
+        syn::Pat::Ident(ident) if ident.ident == "runtime" || ident.ident == "_runtime" => {},
+        _ => return Ok(false),
+    }
+
+    // This checks the type is `&mut T`.
+    let is_mut_t = match &*arg.ty {
+        syn::Type::Reference(reference) => {
+            reference.mutability.is_some() &&
+                matches!(&*reference.elem, syn::Type::Path(tp) if tp.path.is_ident("T"))
+        },
+        _ => false,
+    };
+    if !is_mut_t {
+        let msg = "Invalid type for the runtime parameter: expected `runtime: &mut T`";
+        return Err(syn::Error::new(arg.ty.span(), msg))
+    }
+
+    Ok(true)
+}
+
 /// Check caller arg is exactly: `caller: T::AccountId`.
 ///
 /// This is kept strict to keep the code simple.
@@ -123,7 +240,7 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
     if let syn::Pat::Ident(ident) = &*arg.pat {
         // We also support the name as `_caller` for when the variable is unused.
         if &ident.ident != "caller" && &ident.ident != "_caller" {
-            let msg = "Invalid name for second parameter: expected `caller: T::AccountId`";
+            let msg = "Invalid name for the caller parameter: expected `caller: T::AccountId`";
             return Err(syn::Error::new(ident.span(), msg))
         }
     }
@@ -131,7 +248,7 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
     // This checks the type is `T::AccountId` with `CheckDispatchableFirstArg`
     let ty = &arg.ty;
     syn::parse2::<CheckDispatchableFirstArg>(ty.to_token_stream()).map_err(|e| {
-        let msg = "Invalid type for second parameter: expected `caller: T::AccountId`";
+        let msg = "Invalid type for the caller parameter: expected `caller: T::AccountId`";
         let mut err = syn::Error::new(ty.span(), msg);
         err.combine(e);
         err
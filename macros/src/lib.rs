@@ -1,6 +1,26 @@
 mod call;
 mod runtime;
 
+/// Expand the callable functions of a pallet.
+///
+/// Every function must declare its weight with a `#[weight(...)]` attribute, holding an expression
+/// of type `support::Weight`, which can refer to the arguments of the call by reference. Its
+/// arguments are `&mut self`, an optional `runtime: &mut T` giving access to the rest of the
+/// runtime through the `Config` of the pallet, the `caller: T::AccountId`, and then the arguments
+/// of the call.
+///
+/// Every function must also declare its index with a `#[call_index(...)]` attribute, holding a `u8`
+/// which is unique within the pallet. The index identifies the call in its encoding, so functions
+/// can be reordered without changing the encoding of their calls.
+///
+/// This generates:
+/// - `enum Call` - with one variant for each function of the `impl` block, holding all the arguments
+///   except for the `caller`.
+/// - `fn Call::weight()` - which returns the weight declared for the function.
+/// - `fn dispatch()` on the pallet, routing each `Call` to its function, and passing the runtime to
+///   the functions which take it.
+/// - implements `codec::Encode` and `codec::Decode` for `Call`, as the index of the function
+///   followed by its arguments.
 #[proc_macro_attribute]
 pub fn call(
     attr: proc_macro::TokenStream,
@@ -11,20 +31,58 @@ pub fn call(
 
 /// Expand the `Runtime` definition.
 ///
+/// Every pallet except `system` must declare its index with a `#[pallet_index(...)]` attribute,
+/// holding a `u8` which is unique within the runtime. Index 0 is reserved for the system pallet.
+///
 /// This generates function implementations on `Runtime`:
-/// - `fn new()` - which generates a new instance of the runtime, by instantiating all the pallets
-///   included in the runtime.
+/// - `fn from_genesis()` - which generates a new instance of the runtime, by instantiating all the
+///   pallets included in the runtime and building the `GenesisConfig` of system and then of each
+///   of them in declaration order, and records the hash of the genesis block in the system pallet.
+///   Every pallet module must declare a `GenesisConfig<T>` implementing `Default` and
+///   `support::BuildGenesisConfig`.
+/// - `fn new()` - which generates a new instance of the runtime from the default genesis config.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
 ///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   valid block number, that it builds on the last executed block, and that its extrinsics root
+///   and state root match the computed values. Each extrinsic is dispatched inside a
+///   `support::with_transaction`, so the `Runtime` must implement `Clone`. Before dispatch, the
+///   signature of each extrinsic is verified, the caller is derived from the signer's public key
+///   and the nonce of the extrinsic is checked against the nonce of the caller. Then the
+///   transaction fee is charged by `system::Config::charge_transaction_fee`, which the `Runtime`
+///   implements. A block with an invalid signature or nonce, or a caller who can't pay the fee,
+///   is rejected. After each extrinsic, the events emitted by the pallets are collected with
+///   `take_events()` and recorded in the system pallet together with the extrinsic index. Every
+///   pallet must implement `support::Hooks`: before the extrinsics of a block, the
+///   `on_initialize` hook of each pallet is called in declaration order, and after them, the
+///   `on_finalize` hook. The events emitted by the hooks are recorded with
+///   `system::Phase::Initialization` and `system::Phase::Finalization`.
+/// - `fn build_block()` - which builds the next block from a list of extrinsics, filling in the
+///   header by executing and finalizing them on a copy of the runtime.
+/// - `fn state_root()` - which hashes the encoded state of all the pallets, so every pallet must
+///   implement `codec::Encode`.
 ///
 /// This also generates code needed for dispatching calls to the pallets:
 /// - Note: For simplicity, we assume that the system pallet is not callable.
 /// - `enum RuntimeCall` - an "outer"-enum representing the accumulation of all possible calls to
 ///   all pallets. The system pallet is not included.
-/// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
-///   logic like incrementing the nonce of the user is included in the generated code. The system
-///   pallet is not included.
+/// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. The
+///   system pallet is not included. Dispatching only routes the call: the signature, nonce and fee
+///   of an extrinsic are handled before, see `fn execute_block()`. While its call executes, a
+///   pallet is taken out of the runtime, so the call can be given the rest of the runtime, and
+///   every pallet must implement `fn new()`. The `Error` of each pallet is converted into a
+///   `support::DispatchError::Module` carrying the index of the pallet in the runtime.
+/// - implements `codec::Encode` and `codec::Decode` for `RuntimeCall`, as the index of the pallet
+///   followed by the call.
+/// - `fn RuntimeCall::weight()` - which returns the weight of the pallet call. A block whose calls
+///   weigh more than the maximum block weight of the system pallet is rejected.
+///
+/// The code needed for the genesis state:
+/// - `struct RuntimeGenesisConfig` - with the `GenesisConfig` of each pallet, in a field named
+///   after the pallet. The system pallet is included, with a `GenesisConfig` which is not generic.
+///
+/// And the code needed for events:
+/// - `enum RuntimeEvent` - an "outer"-enum representing the accumulation of all the `Event` enums
+///   declared by the pallets, including the events of the system pallet.
 #[proc_macro_attribute]
 pub fn runtime(
     attr: proc_macro::TokenStream,
@@ -6,41 +6,277 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
     let RuntimeDef { runtime_struct, pallets } = def;
 
     // This is a vector of all the pallet names, not including system.
-    let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+    let pallet_names = pallets.iter().map(|(name, _, _)| name.clone()).collect::<Vec<_>>();
     // This is a vector of all the pallet types, not including system.
-    let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+    let pallet_types = pallets.iter().map(|(_, type_, _)| type_.clone()).collect::<Vec<_>>();
+    // This is a vector of the index of each pallet in the runtime, declared with
+    // `#[pallet_index(...)]` and used to encode the `RuntimeCall` and to identify the pallet which
+    // raised a `DispatchError::Module`. The system pallet always has index 0.
+    let pallet_indices = pallets.iter().map(|(_, _, index)| *index).collect::<Vec<_>>();
 
     // This quote block implements functions on the `Runtime` struct.
     let runtime_impl = quote! {
 		impl #runtime_struct {
-			// Create a new instance of the main Runtime, by creating a new instance of each pallet.
+			// Create a new instance of the main Runtime, with the default genesis config of each
+			// pallet.
 			fn new() -> Self {
-				Self {
+				Self::from_genesis(RuntimeGenesisConfig::default())
+					.expect("the default genesis config is valid")
+			}
+
+			// Create a new instance of the main Runtime, by creating a new instance of each pallet
+			// and building its genesis config, starting with system and then in the order the
+			// pallets are declared.
+			//
+			// The genesis block is the header of block 0 committing to this initial state, and its
+			// hash identifies the chain. The events emitted while building the genesis state are
+			// not recorded.
+			fn from_genesis(
+				config: RuntimeGenesisConfig,
+			) -> Result<Self, crate::support::DispatchError> {
+				let mut runtime = Self {
 					// Since system is not included in the list of pallets, we manually add it here.
 					system: <system::Pallet::<Self>>::new(),
 					#(
 						#pallet_names: <#pallet_types>::new()
 					),*
-				}
+				};
+				let mut pallet = core::mem::replace(
+					&mut runtime.system,
+					<system::Pallet::<Self>>::new(),
+				);
+				let result = crate::support::BuildGenesisConfig::build(
+					&config.system,
+					&mut pallet,
+					&mut runtime,
+				);
+				runtime.system = pallet;
+				result?;
+				#(
+					let mut pallet = core::mem::replace(
+						&mut runtime.#pallet_names,
+						<#pallet_types>::new(),
+					);
+					let result = crate::support::BuildGenesisConfig::build(
+						&config.#pallet_names,
+						&mut pallet,
+						&mut runtime,
+					);
+					runtime.#pallet_names = pallet;
+					result?;
+				)*
+				#( let _ = runtime.#pallet_names.take_events(); )*
+				let genesis = types::Header {
+					parent_hash: crate::support::Hash::default(),
+					block_number: runtime.system.block_number(),
+					extrinsics_root: crate::support::extrinsics_root::<types::Extrinsic>(&[]),
+					state_root: runtime.state_root(),
+				};
+				runtime.system.set_genesis_hash(genesis.hash());
+				Ok(runtime)
 			}
 
 			// Execute a block of extrinsics. Increments the block number.
+			//
+			// The block is rejected if it does not build on the last executed block, or if its
+			// extrinsics root or state root do not match the values we compute. A rejected block
+			// leaves no changes behind.
 			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
-				self.system.inc_block_number();
-				if block.header.block_number != self.system.block_number() {
-					return Err(&"block number does not match what is expected")
+				crate::support::with_transaction(self, |runtime| {
+					runtime.system.inc_block_number();
+					if block.header.block_number != runtime.system.block_number() {
+						return Err(crate::support::DispatchError::Other(
+							"block number does not match what is expected",
+						))
+					}
+					if block.header.parent_hash != runtime.system.parent_hash() {
+						return Err(crate::support::DispatchError::Other(
+							"parent hash does not match the last executed block",
+						))
+					}
+					if block.header.extrinsics_root != crate::support::extrinsics_root(&block.extrinsics) {
+						return Err(crate::support::DispatchError::Other(
+							"extrinsics root does not match the extrinsics of the block",
+						))
+					}
+					runtime.initialize_block();
+					runtime.apply_extrinsics(block.extrinsics)?;
+					runtime.finalize_block();
+					if block.header.state_root != runtime.state_root() {
+						return Err(crate::support::DispatchError::Other(
+							"state root does not match the state after execution",
+						))
+					}
+					runtime.system.set_parent_hash(block.header.hash());
+					Ok(())
+				})
+			}
+
+			// Build the next block on top of the last executed block, by executing `extrinsics` on a
+			// copy of the runtime to compute the roots of the header.
+			fn build_block(
+				&self,
+				extrinsics: Vec<types::Extrinsic>,
+			) -> Result<types::Block, crate::support::DispatchError> {
+				let mut runtime = self.clone();
+				runtime.system.inc_block_number();
+				let extrinsics_root = crate::support::extrinsics_root(&extrinsics);
+				runtime.initialize_block();
+				runtime.apply_extrinsics(extrinsics.clone())?;
+				runtime.finalize_block();
+				let header = types::Header {
+					parent_hash: runtime.system.parent_hash(),
+					block_number: runtime.system.block_number(),
+					extrinsics_root,
+					state_root: runtime.state_root(),
+				};
+				Ok(types::Block { header, extrinsics })
+			}
+
+			// Apply the extrinsics of a block in order.
+			//
+			// A block whose calls weigh more than the maximum block weight is invalid. So is a block
+			// with an extrinsic with an invalid signature, whose nonce does not match the nonce of the
+			// caller, or whose caller can't pay the transaction fee.
+			// Otherwise, the caller is derived from the public key of the signer, its nonce is
+			// incremented, the fee is charged by `system::Config::charge_transaction_fee` and the
+			// call is dispatched.
+			//
+			// Every event emitted while applying an extrinsic is recorded in the system pallet
+			// alongside the extrinsic index. Failed extrinsics are rolled back.
+			fn apply_extrinsics(
+				&mut self,
+				extrinsics: Vec<types::Extrinsic>,
+			) -> crate::support::DispatchResult {
+				use crate::codec::Encode;
+				let weight = extrinsics
+					.iter()
+					.fold(0 as crate::support::Weight, |total, extrinsic| {
+						total.saturating_add(extrinsic.call.weight())
+					});
+				if weight > self.system.max_block_weight() {
+					return Err(crate::support::DispatchError::Other(
+						"block weight exceeds the maximum block weight",
+					))
 				}
-				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
+				let genesis_hash = self.system.genesis_hash();
+				for (i, extrinsic) in extrinsics.into_iter().enumerate() {
+					let i = i as u32;
+					if !extrinsic.verify(&genesis_hash) {
+						return Err(crate::support::DispatchError::Other("invalid signature"))
+					}
+					let len = extrinsic.encode().len() as u64;
+					let weight = extrinsic.call.weight();
+					let support::Extrinsic { signer, nonce, call, .. } = extrinsic;
+					let caller = <Self as system::Config>::AccountId::from(signer);
+					if nonce != self.system.nonce(&caller) {
+						return Err(crate::support::DispatchError::Other(
+							"nonce does not match the nonce of the caller",
+						))
+					}
 					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
-						eprintln!(
-							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
-							block.header.block_number, i, e
-						)
+					// The fee is charged before dispatch, and is not refunded if the call fails.
+					<Self as system::Config>::charge_transaction_fee(self, &caller, len, weight)
+						.map_err(|_| {
+							crate::support::DispatchError::Other("caller cannot pay the transaction fee")
+						})?;
+					// Every extrinsic is dispatched in its own storage transaction, so a failed
+					// call leaves no partial changes, nor any of the events it emitted. The events
+					// of the block are moved out of the runtime meanwhile, so the transaction doesn't
+					// copy them for every extrinsic.
+					let events = self.system.take_events();
+					let result = crate::support::with_transaction(self, |runtime| {
+						runtime.dispatch(caller, call)
 					});
+					self.system.restore_events(events);
+					let phase = system::Phase::ApplyExtrinsic(i);
+					self.collect_events(phase);
+					let event = match result {
+						Ok(()) => system::Event::ExtrinsicSuccess,
+						Err(error) => system::Event::ExtrinsicFailed { error },
+					};
+					self.system.deposit_event(phase, RuntimeEvent::system(event));
 				}
 				Ok(())
 			}
+
+			// Clear the events of the previous block, and call the `on_initialize` hook of every
+			// pallet, in the order they are declared, before the extrinsics of a block.
+			//
+			// Like a call, each hook is given the rest of the runtime, while its pallet is taken out
+			// of it. Blocks are initialized and finalized in the same way when they are built and
+			// executed, so both compute the same state root.
+			fn initialize_block(&mut self) {
+				self.system.reset_events();
+				let block_number = self.system.block_number();
+				#(
+					let mut pallet = core::mem::replace(
+						&mut self.#pallet_names,
+						<#pallet_types>::new(),
+					);
+					crate::support::Hooks::on_initialize(&mut pallet, self, block_number);
+					self.#pallet_names = pallet;
+				)*
+				self.collect_events(system::Phase::Initialization);
+			}
+
+			// Call the `on_finalize` hook of every pallet, in the order they are declared, after the
+			// extrinsics of a block.
+			fn finalize_block(&mut self) {
+				let block_number = self.system.block_number();
+				#(
+					let mut pallet = core::mem::replace(
+						&mut self.#pallet_names,
+						<#pallet_types>::new(),
+					);
+					crate::support::Hooks::on_finalize(&mut pallet, self, block_number);
+					self.#pallet_names = pallet;
+				)*
+				self.collect_events(system::Phase::Finalization);
+			}
+
+			// Move the events emitted by the pallets into the system pallet, recording the phase of
+			// the block in which they were emitted.
+			fn collect_events(&mut self, phase: system::Phase) {
+				#(
+					for event in self.#pallet_names.take_events() {
+						self.system.deposit_event(phase, RuntimeEvent::#pallet_names(event));
+					}
+				)*
+			}
+
+			// The hash of the encoded state of all the pallets.
+			fn state_root(&self) -> crate::support::Hash {
+				use crate::codec::Encode;
+				let mut state = Vec::new();
+				self.system.encode_to(&mut state);
+				#( self.#pallet_names.encode_to(&mut state); )*
+				crate::support::hash(&state)
+			}
+		}
+	};
+
+    // This quote block implements the `RuntimeGenesisConfig` struct.
+    let genesis_impl = quote! {
+		// The initial state of the runtime, built by `Runtime::from_genesis`.
+		// Note that it is just an accumulation of the `GenesisConfig` of each pallet, including
+		// system.
+		#[derive(Debug, Clone, Default)]
+		pub struct RuntimeGenesisConfig {
+			pub system: system::GenesisConfig,
+			#( pub #pallet_names: #pallet_names::GenesisConfig<#runtime_struct> ),*
+		}
+	};
+
+    // This quote block implements the `RuntimeEvent` enum.
+    let event_impl = quote! {
+		// These are all the events which can be emitted by the runtime.
+		// Note that it is just an accumulation of the events of each pallet, including system.
+		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone)]
+		pub enum RuntimeEvent {
+			system(system::Event),
+			#( #pallet_names(#pallet_names::Event<#runtime_struct>) ),*
 		}
 	};
 
@@ -51,14 +287,57 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone)]
 		pub enum RuntimeCall {
 			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
 		}
 
+		impl RuntimeCall {
+			// The weight of the call, as declared by the pallet.
+			pub fn weight(&self) -> crate::support::Weight {
+				match self {
+					#(
+						RuntimeCall::#pallet_names(call) => call.weight(),
+					)*
+				}
+			}
+		}
+
+		// A runtime call is encoded as the index of the pallet, followed by the pallet call, and
+		// decoded the same way.
+		impl crate::codec::Encode for RuntimeCall {
+			fn encode_to(&self, dest: &mut Vec<u8>) {
+				match self {
+					#(
+						RuntimeCall::#pallet_names(call) => {
+							dest.push(#pallet_indices);
+							call.encode_to(dest);
+						}
+					),*
+				}
+			}
+		}
+
+		impl crate::codec::Decode for RuntimeCall {
+			fn decode_from(input: &mut &[u8]) -> Result<Self, crate::codec::Error> {
+				match <u8 as crate::codec::Decode>::decode_from(input)? {
+					#(
+						#pallet_indices => Ok(RuntimeCall::#pallet_names(
+							crate::codec::Decode::decode_from(input)?,
+						)),
+					)*
+					_ => Err(crate::codec::Error::Invalid("unknown pallet")),
+				}
+			}
+		}
+
 		impl crate::support::Dispatch for #runtime_struct {
 			type Caller = <Runtime as system::Config>::AccountId;
 			type Call = RuntimeCall;
-			// Dispatch a call on behalf of a caller. Increments the caller's nonce.
+			type Error = crate::support::DispatchError;
+			// Dispatch a call on behalf of a caller. The caller's nonce has already been checked and
+			// incremented when the extrinsic was applied, and the call can read the rest of the
+			// runtime, such as the block number of the system pallet.
 			//
 			// Dispatch allows us to identify which underlying pallet call we want to execute.
 			// Note that we extract the `caller` from the extrinsic, and use that information
@@ -67,13 +346,26 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 				&mut self,
 				caller: Self::Caller,
 				runtime_call: Self::Call,
-			) -> crate::support::DispatchResult {
+			) -> crate::support::DispatchResult<Self::Error> {
 				// This match statement will allow us to correctly route `RuntimeCall`s
-				// to the appropriate pallet level call.
+				// to the appropriate pallet level call. Errors of the pallets are tagged with the
+				// index of the pallet which raised them.
+				//
+				// The pallet is taken out of the runtime while its call executes, so the call can be
+				// given access to the rest of the runtime, for example to read the block number from
+				// the system pallet.
 				match runtime_call {
 					#(
 						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+							let mut pallet = core::mem::replace(
+								&mut self.#pallet_names,
+								<#pallet_types>::new(),
+							);
+							let result = pallet.dispatch(self, caller, call);
+							self.#pallet_names = pallet;
+							result.map_err(|error| {
+								crate::support::DispatchError::module(#pallet_indices, error)
+							})?;
 						}
 					),*
 				}
@@ -85,6 +377,8 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
     // We combine and return all the generated code.
     quote! {
 		#dispatch_impl
+		#event_impl
+		#genesis_impl
 		#runtime_impl
 	}
     // .into()
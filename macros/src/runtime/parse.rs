@@ -6,8 +6,9 @@ pub struct RuntimeDef {
     /// This is the name of the struct used by the user. We mostly assume it is `Runtime`.
     pub runtime_struct: syn::Ident,
     /// This is the list of pallets included in the `Runtime` struct. We omit `system` from this
-    /// list, but during parsing we check that system exists.
-    pub pallets: Vec<(syn::Ident, syn::Type)>,
+    /// list, but during parsing we check that system exists. Each pallet comes with the index
+    /// declared with `#[pallet_index(...)]`, which identifies it in encodings and errors.
+    pub pallets: Vec<(syn::Ident, syn::Type, u8)>,
 }
 
 impl RuntimeDef {
@@ -22,14 +23,37 @@ impl RuntimeDef {
         // We check that the `Runtime` includes the `system` pallet as the first item.
         check_system(&item_struct)?;
 
+        // The system pallet always has index 0, so it can't declare one.
+        if let Some(field) = item_struct.fields.iter().next() {
+            let mut attrs = field.attrs.iter();
+            if let Some(attr) = attrs.find(|attr| attr.path().is_ident("pallet_index")) {
+                let msg = "Invalid runtime, the system pallet always has the pallet index 0";
+                return Err(syn::Error::new_spanned(attr, msg))
+            }
+        }
+
         let runtime_struct = item_struct.ident;
 
         // Here is where we will store a list of all the pallets.
         let mut pallets = vec![];
         // We skip `system`, which we ensure is the first field in `check_system`.
         for field in item_struct.fields.into_iter().skip(1) {
+            let index = parse_pallet_index(&field)?;
+            let pallet_index = index.base10_parse::<u8>()?;
+            // Index 0 is reserved for the system pallet, and no two pallets can share an index.
+            if pallet_index == 0 {
+                let msg = "Invalid runtime, the pallet index 0 is reserved for system";
+                return Err(syn::Error::new(index.span(), msg))
+            }
+            if let Some((other, _, _)) = pallets.iter().find(|(_, _, i)| *i == pallet_index) {
+                let msg = format!(
+                    "Invalid runtime, the pallet index {} is already used by `{}`",
+                    pallet_index, other
+                );
+                return Err(syn::Error::new(index.span(), msg))
+            }
             if let Some(ident) = field.ident {
-                pallets.push((ident, field.ty))
+                pallets.push((ident, field.ty, pallet_index))
             }
         }
 
@@ -37,6 +61,36 @@ impl RuntimeDef {
     }
 }
 
+/// Extract the index of the `#[pallet_index(...)]` attribute of a pallet in the `Runtime` struct.
+fn parse_pallet_index(field: &syn::Field) -> syn::Result<syn::LitInt> {
+    let mut indices = field.attrs.iter().filter(|attr| attr.path().is_ident("pallet_index"));
+
+    let index = match indices.next() {
+        Some(attr) => attr.parse_args::<syn::LitInt>()?,
+        None => {
+            let msg = "Invalid runtime, expected a `#[pallet_index(...)]` attribute";
+            return Err(syn::Error::new(field.span(), msg))
+        },
+    };
+
+    if let Some(attr) = indices.next() {
+        let msg = "Invalid runtime, only one `#[pallet_index(...)]` attribute is allowed";
+        return Err(syn::Error::new(attr.span(), msg))
+    }
+
+    Ok(index)
+}
+
+/// Remove the attributes which only have a meaning for this macro, like `#[pallet_index(...)]`,
+/// since they are not valid Rust attributes.
+pub fn strip_runtime_attributes(item: &mut syn::Item) {
+    if let syn::Item::Struct(item_struct) = item {
+        for field in item_struct.fields.iter_mut() {
+            field.attrs.retain(|attr| !attr.path().is_ident("pallet_index"));
+        }
+    }
+}
+
 /// This function checks that the `system` pallet is the first pallet included in the `Runtime`
 /// struct. We make many assumptions about the `system` pallet in order to keep these macros simple.
 /// For example, we assume that the system pallet has no callable functions, and that it contains
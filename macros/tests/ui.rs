@@ -0,0 +1,7 @@
+// The compile errors of the macros for invalid call and pallet indices. Run with
+// `TRYBUILD=overwrite` to update the expected errors after changing them.
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
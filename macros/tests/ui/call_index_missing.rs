@@ -0,0 +1,27 @@
+#![allow(dead_code)]
+
+pub trait Config {
+    type AccountId;
+}
+
+pub struct Pallet<T: Config>(core::marker::PhantomData<T>);
+
+pub enum Error {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    #[call_index(0)]
+    #[weight(1)]
+    pub fn first(&mut self, caller: T::AccountId) -> Result<(), Error> {
+        let _ = caller;
+        Ok(())
+    }
+
+    #[weight(1)]
+    pub fn second(&mut self, caller: T::AccountId) -> Result<(), Error> {
+        let _ = caller;
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+#![allow(dead_code)]
+
+struct System;
+struct Balances;
+struct Claims;
+
+#[macros::runtime]
+pub struct Runtime {
+    #[pallet_index(0)]
+    system: System,
+    #[pallet_index(1)]
+    balances: Balances,
+    #[pallet_index(2)]
+    claims: Claims,
+}
+
+fn main() {}
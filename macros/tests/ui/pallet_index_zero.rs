@@ -0,0 +1,16 @@
+#![allow(dead_code)]
+
+struct System;
+struct Balances;
+struct Claims;
+
+#[macros::runtime]
+pub struct Runtime {
+    system: System,
+    #[pallet_index(0)]
+    balances: Balances,
+    #[pallet_index(1)]
+    claims: Claims,
+}
+
+fn main() {}
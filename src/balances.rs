@@ -1,17 +1,154 @@
-use crate::support::DispatchResult;
-use num::traits::{CheckedAdd, CheckedSub, Zero};
+use crate::codec::Encode;
+use crate::support::{
+    BuildGenesisConfig, Currency, DispatchError, DispatchResult, Hooks, PalletError, Weight,
+};
+use core::fmt::Debug;
+use num::traits::{CheckedAdd, CheckedMul, CheckedSub, Zero};
 use std::collections::BTreeMap;
 
 /* In the Polkadot SDK ecosystem, we call this "tight coupling" because a runtime which contains the Balances Pallet must also contain the System Pallet.
    In a sense these two pallets are tightly coupled to one another.
 */
 pub trait Config: crate::system::Config {
-    type Balance: CheckedAdd + CheckedSub + Zero + Copy;
+    type Balance: CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + Zero
+        + Copy
+        + Debug
+        + PartialOrd
+        + Encode
+        + From<u64>;
+
+    /// The default minimum balance an account must hold to exist, which the genesis config of a
+    /// chain can override. An account whose balance drops below it is reaped, which removes it
+    /// from storage.
+    const EXISTENTIAL_DEPOSIT: Self::Balance;
+
+    /// The fee charged for every extrinsic.
+    const BASE_FEE: Self::Balance;
+    /// The fee charged for every byte of an encoded extrinsic.
+    const BYTE_FEE: Self::Balance;
+    /// The fee charged for every unit of weight of a call.
+    const WEIGHT_FEE: Self::Balance;
+
+    /// The account credited with the fees, or `None` if the fees are burned.
+    fn fee_collector() -> Option<Self::AccountId>;
+    /// The account credited with the dust left by reaped accounts, or `None` if the dust is
+    /// burned.
+    fn dust_collector() -> Option<Self::AccountId>;
+}
+
+/// The events emitted by the Balances Pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+    /// `amount` was transferred from `from` to `to`.
+    Transfer {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `who` paid a transaction fee of `amount`.
+    FeePaid {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `who` was reaped, and its remaining balance `dust` was handled by the dust policy.
+    AccountReaped { who: T::AccountId, dust: T::Balance },
+    /// `amount` was moved from the free balance of `who` to its reserved balance.
+    Reserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `amount` was moved from the reserved balance of `who` back to its free balance.
+    Unreserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `amount` of the reserved balance of `who` was slashed, and left the total issuance.
+    ReserveSlashed {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `amount` of the reserved balance of `from` was moved to `to`, into the balance given by
+    /// `status`.
+    ReserveRepatriated {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+        status: BalanceStatus,
+    },
+    /// The lock `id` on the free balance of `who` was set to `amount`.
+    LockSet {
+        who: T::AccountId,
+        id: LockIdentifier,
+        amount: T::Balance,
+    },
+    /// The lock `id` on the free balance of `who` was removed.
+    LockRemoved {
+        who: T::AccountId,
+        id: LockIdentifier,
+    },
+}
+
+/// The identifier of a lock, which lets several pallets lock the same funds independently.
+pub type LockIdentifier = [u8; 8];
+
+/// Which balance of the beneficiary receives a repatriated reserve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceStatus {
+    Free,
+    Reserved,
+}
+
+/// The errors which can be returned by the calls of the Balances Pallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The caller does not have enough balance to make the transfer.
+    InsufficientBalance,
+    /// The balance of the receiver would overflow.
+    Overflow,
+    /// The receiver would be left with less than the existential deposit.
+    ExistentialDeposit,
+    /// The transfer would reap the caller, which `transfer_keep_alive` refuses to do.
+    KeepAlive,
+    /// The account does not have enough reserved balance.
+    InsufficientReserved,
+    /// The free balance would drop below the largest lock on it.
+    LiquidityRestrictions,
+}
+
+impl PalletError for Error {
+    fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Error::InsufficientBalance => "Insufficient balance",
+            Error::Overflow => "Overflow",
+            Error::ExistentialDeposit => "Balance below the existential deposit",
+            Error::KeepAlive => "Transfer would reap the caller",
+            Error::InsufficientReserved => "Insufficient reserved balance",
+            Error::LiquidityRestrictions => "Balance is locked",
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
     balances: BTreeMap<T::AccountId, T::Balance>,
+    /// The reserved balance of each account, which can't be transferred.
+    reserved: BTreeMap<T::AccountId, T::Balance>,
+    /// The named locks on the free balance of each account. Locks overlap, so the amount which
+    /// can't be moved is the largest of them.
+    locks: BTreeMap<T::AccountId, BTreeMap<LockIdentifier, T::Balance>>,
+    /// The sum of the free and reserved balances of all the accounts.
+    total_issuance: T::Balance,
+    /// The minimum balance an account must hold to exist.
+    existential_deposit: T::Balance,
+    /// Events emitted by this pallet which have not yet been collected by the runtime.
+    events: Vec<Event<T>>,
 }
 
 #[macros::call]
@@ -19,25 +156,29 @@ impl<T: Config> Pallet<T> {
     /// Transfere `amount` de uma conta para outra.
     /// Esta função verifica se `caller` tem pelo menos `amount` de saldo para transferir,
     /// e se não ocorrem overflow/underflow matemáticos.
+    /// Se o saldo restante de `caller` ficar abaixo do depósito existencial, a conta é removida.
+    #[call_index(0)]
+    #[weight(10)]
     pub fn transfer(
         &mut self,
         caller: T::AccountId,
         to: T::AccountId,
         amount: T::Balance,
-    ) -> DispatchResult {
-        let caller_balance = self.get_balance(caller.clone());
-        let to_balance = self.get_balance(to.clone());
-
-        let new_caller_balance = caller_balance
-            .checked_sub(&amount)
-            .ok_or("Insufficient balance")?; // Underflow
-
-        let new_to_balance = to_balance.checked_add(&amount).ok_or("Overflow")?;
-
-        self.balances.insert(caller, new_caller_balance);
-        self.balances.insert(to, new_to_balance);
+    ) -> DispatchResult<Error> {
+        self.do_transfer(caller, to, amount, false)
+    }
 
-        Ok(())
+    /// Transfer `amount` from the caller to `to`, like `transfer`, but fail instead of reaping the
+    /// caller.
+    #[call_index(1)]
+    #[weight(10)]
+    pub fn transfer_keep_alive(
+        &mut self,
+        caller: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult<Error> {
+        self.do_transfer(caller, to, amount, true)
     }
 }
 
@@ -45,11 +186,97 @@ impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
         Pallet {
             balances: BTreeMap::new(),
+            reserved: BTreeMap::new(),
+            locks: BTreeMap::new(),
+            total_issuance: T::Balance::zero(),
+            existential_deposit: T::EXISTENTIAL_DEPOSIT,
+            events: Vec::new(),
         }
     }
 
+    /// Set the free balance of `account`, adjusting the total issuance by the difference. Setting
+    /// a balance which doesn't keep the account alive removes it. Panics if the total issuance
+    /// would overflow.
     pub fn set_balance(&mut self, account: T::AccountId, amount: T::Balance) {
-        self.balances.insert(account, amount);
+        let amount = if !self.keeps_alive(&account, amount) {
+            T::Balance::zero()
+        } else {
+            amount
+        };
+        self.total_issuance = self
+            .total_issuance
+            .checked_sub(&self.get_balance(account.clone()))
+            .and_then(|issuance| issuance.checked_add(&amount))
+            .expect("total issuance overflow");
+        if amount.is_zero() {
+            self.balances.remove(&account);
+        } else {
+            self.balances.insert(account, amount);
+        }
+    }
+
+    /// The sum of the free and reserved balances of all the accounts.
+    pub fn total_issuance(&self) -> T::Balance {
+        self.total_issuance
+    }
+
+    /// The minimum balance an account must hold to exist.
+    pub fn existential_deposit(&self) -> T::Balance {
+        self.existential_deposit
+    }
+
+    /// Create `amount` of new money in the account of `who`, increasing the total issuance.
+    /// This is a privileged operation, which is not exposed as a call.
+    pub fn mint(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), Error> {
+        if self.total_issuance.checked_add(&amount).is_none() {
+            return Err(Error::Overflow);
+        }
+        let new_balance = self
+            .get_balance(who.clone())
+            .checked_add(&amount)
+            .ok_or(Error::Overflow)?;
+        if !self.keeps_alive(&who, new_balance) {
+            return Err(Error::ExistentialDeposit);
+        }
+
+        self.set_balance(who, new_balance);
+        Ok(())
+    }
+
+    /// Destroy `amount` of money from the account of `who`, decreasing the total issuance. The
+    /// account is reaped if it is left below the existential deposit. Locked funds can't be burned.
+    /// This is a privileged operation, which is not exposed as a call.
+    pub fn burn(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), Error> {
+        let new_balance = self
+            .get_balance(who.clone())
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientBalance)?;
+        self.ensure_can_withdraw(&who, new_balance)?;
+
+        self.reduce_issuance(amount);
+        self.write_balance(who, new_balance);
+        Ok(())
+    }
+
+    /// Check the invariant of this pallet: the free and reserved balances of all the accounts add
+    /// up to the total issuance.
+    pub fn check_total_issuance(&self) -> bool {
+        self.balances
+            .values()
+            .chain(self.reserved.values())
+            .try_fold(T::Balance::zero(), |total, balance| {
+                total.checked_add(balance)
+            })
+            .is_some_and(|total| total == self.total_issuance)
+    }
+
+    // `amount` always comes out of an account, and the total issuance is at least the balance of
+    // any account, so an underflow here means the books no longer add up.
+    fn reduce_issuance(&mut self, amount: T::Balance) {
+        self.total_issuance = self
+            .total_issuance
+            .checked_sub(&amount)
+            .expect("total issuance covers every balance");
     }
 
     /*
@@ -60,22 +287,501 @@ impl<T: Config> Pallet<T> {
     pub fn get_balance(&self, account: T::AccountId) -> T::Balance {
         *self.balances.get(&account).unwrap_or(&T::Balance::zero())
     }
+
+    /// The reserved balance of `account`.
+    pub fn reserved_balance(&self, account: &T::AccountId) -> T::Balance {
+        *self.reserved.get(account).unwrap_or(&T::Balance::zero())
+    }
+
+    /// Move `amount` from the free balance of `who` to its reserved balance, where it can't be
+    /// transferred. The account is kept alive by its reserved balance, unless it has locks: the
+    /// reserve may be slashed or unreserved later, and a locked account must not be reaped then,
+    /// so its free balance must stay at the existential deposit on its own.
+    pub fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> Result<(), Error> {
+        let new_free = self
+            .get_balance(who.clone())
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientBalance)?;
+        let locked = self.locked_balance(who);
+        if new_free < locked || (!locked.is_zero() && new_free < self.existential_deposit) {
+            return Err(Error::LiquidityRestrictions);
+        }
+        let new_reserved = self
+            .reserved_balance(who)
+            .checked_add(&amount)
+            .ok_or(Error::Overflow)?;
+
+        self.write_reserved(who.clone(), new_reserved);
+        self.write_balance(who.clone(), new_free);
+        self.deposit_event(Event::Reserved {
+            who: who.clone(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Move `amount` from the reserved balance of `who` back to its free balance.
+    pub fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> Result<(), Error> {
+        let new_reserved = self
+            .reserved_balance(who)
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientReserved)?;
+        let new_free = self
+            .get_balance(who.clone())
+            .checked_add(&amount)
+            .ok_or(Error::Overflow)?;
+
+        self.write_reserved(who.clone(), new_reserved);
+        self.deposit_event(Event::Unreserved {
+            who: who.clone(),
+            amount,
+        });
+        self.write_balance(who.clone(), new_free);
+        Ok(())
+    }
+
+    /// Destroy `amount` of the reserved balance of `who`, decreasing the total issuance. This is
+    /// how a deposit is confiscated.
+    pub fn slash_reserved(&mut self, who: &T::AccountId, amount: T::Balance) -> Result<(), Error> {
+        let new_reserved = self
+            .reserved_balance(who)
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientReserved)?;
+
+        self.write_reserved(who.clone(), new_reserved);
+        self.reduce_issuance(amount);
+        self.deposit_event(Event::ReserveSlashed {
+            who: who.clone(),
+            amount,
+        });
+        // The account may only have been kept alive by its reserved balance.
+        self.write_balance(who.clone(), self.get_balance(who.clone()));
+        Ok(())
+    }
+
+    /// Move `amount` of the reserved balance of `slashed` to `beneficiary`, into its free or
+    /// reserved balance depending on `status`.
+    pub fn repatriate_reserved(
+        &mut self,
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        amount: T::Balance,
+        status: BalanceStatus,
+    ) -> Result<(), Error> {
+        let new_reserved = self
+            .reserved_balance(slashed)
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientReserved)?;
+
+        // Repatriating to oneself is either unreserving, or changes nothing.
+        if slashed == beneficiary {
+            return match status {
+                BalanceStatus::Free => self.unreserve(slashed, amount),
+                BalanceStatus::Reserved => Ok(()),
+            };
+        }
+
+        let credit = match status {
+            BalanceStatus::Free => self.get_balance(beneficiary.clone()),
+            BalanceStatus::Reserved => self.reserved_balance(beneficiary),
+        }
+        .checked_add(&amount)
+        .ok_or(Error::Overflow)?;
+        if status == BalanceStatus::Free && !self.keeps_alive(beneficiary, credit) {
+            return Err(Error::ExistentialDeposit);
+        }
+
+        self.write_reserved(slashed.clone(), new_reserved);
+        match status {
+            BalanceStatus::Free => self.balances.insert(beneficiary.clone(), credit),
+            BalanceStatus::Reserved => self.reserved.insert(beneficiary.clone(), credit),
+        };
+        self.deposit_event(Event::ReserveRepatriated {
+            from: slashed.clone(),
+            to: beneficiary.clone(),
+            amount,
+            status,
+        });
+        // The account may only have been kept alive by its reserved balance.
+        self.write_balance(slashed.clone(), self.get_balance(slashed.clone()));
+        Ok(())
+    }
+
+    /// The amount of the free balance of `account` which can't be transferred, reserved, burned or
+    /// spent on fees: the largest of its locks.
+    pub fn locked_balance(&self, account: &T::AccountId) -> T::Balance {
+        self.locks
+            .get(account)
+            .into_iter()
+            .flat_map(|locks| locks.values())
+            .fold(
+                T::Balance::zero(),
+                |max, &amount| {
+                    if amount > max {
+                        amount
+                    } else {
+                        max
+                    }
+                },
+            )
+    }
+
+    /// Set the lock `id` on the free balance of `who` to `amount`, replacing any previous lock with
+    /// the same id. A lock may be larger than the free balance. Setting a lock of zero removes it.
+    pub fn set_lock(&mut self, id: LockIdentifier, who: &T::AccountId, amount: T::Balance) {
+        if amount.is_zero() {
+            self.remove_lock(id, who);
+            return;
+        }
+        self.locks
+            .entry(who.clone())
+            .or_default()
+            .insert(id, amount);
+        self.deposit_event(Event::LockSet {
+            who: who.clone(),
+            id,
+            amount,
+        });
+    }
+
+    /// Set the lock `id` on the free balance of `who` to `amount`, unless it already locks more.
+    pub fn extend_lock(&mut self, id: LockIdentifier, who: &T::AccountId, amount: T::Balance) {
+        let current = self
+            .locks
+            .get(who)
+            .and_then(|locks| locks.get(&id))
+            .copied()
+            .unwrap_or_else(T::Balance::zero);
+        if amount > current {
+            self.set_lock(id, who, amount);
+        }
+    }
+
+    /// Remove the lock `id` from the free balance of `who`, if there is one.
+    pub fn remove_lock(&mut self, id: LockIdentifier, who: &T::AccountId) {
+        let Some(locks) = self.locks.get_mut(who) else {
+            return;
+        };
+        if locks.remove(&id).is_none() {
+            return;
+        }
+        if locks.is_empty() {
+            self.locks.remove(who);
+        }
+        self.deposit_event(Event::LockRemoved {
+            who: who.clone(),
+            id,
+        });
+    }
+
+    /// Check that the free balance of `who` can drop to `new_free`: it must stay at least the
+    /// locked balance, and an account with locks can't be reaped, since its locked funds would go
+    /// to the dust collector.
+    fn ensure_can_withdraw(&self, who: &T::AccountId, new_free: T::Balance) -> Result<(), Error> {
+        let locked = self.locked_balance(who);
+        if new_free < locked || (!locked.is_zero() && !self.keeps_alive(who, new_free)) {
+            return Err(Error::LiquidityRestrictions);
+        }
+        Ok(())
+    }
+
+    fn write_reserved(&mut self, who: T::AccountId, amount: T::Balance) {
+        if amount.is_zero() {
+            self.reserved.remove(&who);
+        } else {
+            self.reserved.insert(who, amount);
+        }
+    }
+
+    /// An account stays alive while its free balance is at least the existential deposit, or
+    /// while it has a reserved balance.
+    fn keeps_alive(&self, who: &T::AccountId, free: T::Balance) -> bool {
+        free >= self.existential_deposit || !self.reserved_balance(who).is_zero()
+    }
+
+    fn do_transfer(
+        &mut self,
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+        keep_alive: bool,
+    ) -> DispatchResult<Error> {
+        let new_from_balance = self
+            .get_balance(from.clone())
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientBalance)?; // Underflow
+
+        // A transfer to oneself changes nothing.
+        if from == to {
+            return Ok(());
+        }
+        self.ensure_can_withdraw(&from, new_from_balance)?;
+
+        let new_to_balance = self
+            .get_balance(to.clone())
+            .checked_add(&amount)
+            .ok_or(Error::Overflow)?;
+        if !self.keeps_alive(&to, new_to_balance) {
+            return Err(Error::ExistentialDeposit);
+        }
+        if keep_alive && !self.keeps_alive(&from, new_from_balance) {
+            return Err(Error::KeepAlive);
+        }
+
+        self.balances.insert(to.clone(), new_to_balance);
+        self.deposit_event(Event::Transfer {
+            from: from.clone(),
+            to,
+            amount,
+        });
+        self.write_balance(from, new_from_balance);
+
+        Ok(())
+    }
+
+    /// Write the free balance of `who` after it changed. If it doesn't keep the account alive, the
+    /// account is reaped instead, and its dust goes to the dust collector. If the dust collector
+    /// can't receive it, or would be left below the existential deposit, the dust is burned and
+    /// leaves the total issuance.
+    fn write_balance(&mut self, who: T::AccountId, balance: T::Balance) {
+        if self.keeps_alive(&who, balance) {
+            if balance.is_zero() {
+                self.balances.remove(&who);
+            } else {
+                self.balances.insert(who, balance);
+            }
+            return;
+        }
+
+        // The locks of a reaped account go away with it. An account emptied of its whole balance
+        // leaves no dust behind.
+        self.locks.remove(&who);
+        self.balances.remove(&who);
+        if balance.is_zero() {
+            return;
+        }
+        // Like the fee collector, the dust collector must be left with at least the existential
+        // deposit.
+        let credit = T::dust_collector().and_then(|collector| {
+            let credit = self.get_balance(collector.clone()).checked_add(&balance)?;
+            self.keeps_alive(&collector, credit)
+                .then_some((collector, credit))
+        });
+        match credit {
+            Some((collector, credit)) => {
+                self.balances.insert(collector, credit);
+            }
+            None => self.reduce_issuance(balance),
+        }
+        self.deposit_event(Event::AccountReaped { who, dust: balance });
+    }
+
+    /// The fee of an extrinsic of `len` bytes dispatching a call of the given `weight`:
+    /// `BASE_FEE + BYTE_FEE * len + WEIGHT_FEE * weight`.
+    pub fn compute_fee(len: u64, weight: Weight) -> Option<T::Balance> {
+        let byte_fee = T::BYTE_FEE.checked_mul(&len.into())?;
+        let weight_fee = T::WEIGHT_FEE.checked_mul(&weight.into())?;
+        T::BASE_FEE.checked_add(&byte_fee)?.checked_add(&weight_fee)
+    }
+
+    /// Withdraw the fee of an extrinsic from `who` before it is dispatched, and credit it to the
+    /// fee collector if there is one, or burn it otherwise. Like the receiver of a transfer, the
+    /// collector must be left with at least the existential deposit. Locked funds can't pay the
+    /// fee. Nothing changes if `who` can't pay the fee, or if the collector can't receive it.
+    pub fn charge_fee(
+        &mut self,
+        who: &T::AccountId,
+        len: u64,
+        weight: Weight,
+    ) -> Result<T::Balance, Error> {
+        let fee = Self::compute_fee(len, weight).ok_or(Error::Overflow)?;
+        let new_balance = self
+            .get_balance(who.clone())
+            .checked_sub(&fee)
+            .ok_or(Error::InsufficientBalance)?;
+
+        let collector = T::fee_collector();
+        if collector.as_ref() == Some(who) {
+            // A collector paying the fee to itself keeps its balance.
+            self.deposit_event(Event::FeePaid {
+                who: who.clone(),
+                amount: fee,
+            });
+            return Ok(fee);
+        }
+        self.ensure_can_withdraw(who, new_balance)?;
+        // Check that the collector can receive the fee before writing anything.
+        if let Some(collector) = &collector {
+            let balance = self
+                .get_balance(collector.clone())
+                .checked_add(&fee)
+                .ok_or(Error::Overflow)?;
+            if !self.keeps_alive(collector, balance) {
+                return Err(Error::ExistentialDeposit);
+            }
+        }
+
+        self.deposit_event(Event::FeePaid {
+            who: who.clone(),
+            amount: fee,
+        });
+        match collector {
+            Some(collector) => {
+                self.write_balance(who.clone(), new_balance);
+                // The collector is read after `who` is written, since it may also collect the dust
+                // of `who`. Both the fee and the dust come out of the total issuance, so this
+                // can't overflow.
+                let balance = self
+                    .get_balance(collector.clone())
+                    .checked_add(&fee)
+                    .expect("balances add up to the total issuance");
+                self.balances.insert(collector, balance);
+            }
+            None => self.burn(who.clone(), fee)?,
+        }
+
+        Ok(fee)
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    /// Drain the events emitted since the last call. The runtime uses this to move them into the
+    /// System Pallet after each extrinsic.
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Other pallets use the Balances Pallet through the `Currency` trait, which fails with the same
+/// `Error` as the calls. Like them, it never withdraws locked funds.
+impl<T: Config> Currency<T::AccountId> for Pallet<T> {
+    type Balance = T::Balance;
+    type Error = Error;
+
+    fn free_balance(&self, who: &T::AccountId) -> T::Balance {
+        self.get_balance(who.clone())
+    }
+
+    fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+        Pallet::reserved_balance(self, who)
+    }
+
+    fn transfer(
+        &mut self,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult<Error> {
+        self.do_transfer(from.clone(), to.clone(), amount, false)
+    }
+
+    fn withdraw(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult<Error> {
+        self.burn(who.clone(), amount)
+    }
+
+    fn deposit(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult<Error> {
+        self.mint(who.clone(), amount)
+    }
+
+    fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult<Error> {
+        Pallet::reserve(self, who, amount)
+    }
+
+    fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult<Error> {
+        Pallet::unreserve(self, who, amount)
+    }
+}
+
+/// The initial balances of the chain.
+#[derive(Debug, Clone)]
+pub struct GenesisConfig<T: Config> {
+    /// The accounts endowed with some free balance, which counts towards the total issuance.
+    pub balances: Vec<(T::AccountId, T::Balance)>,
+    /// The minimum balance an account must hold to exist, or `None` for
+    /// `Config::EXISTENTIAL_DEPOSIT`.
+    pub existential_deposit: Option<T::Balance>,
+}
+
+impl<T: Config> Default for GenesisConfig<T> {
+    fn default() -> Self {
+        GenesisConfig {
+            balances: Vec::new(),
+            existential_deposit: None,
+        }
+    }
+}
+
+/// Every account must be endowed at most once, with at least the existential deposit, which can't
+/// be zero.
+impl<T: Config, Runtime> BuildGenesisConfig<Pallet<T>, Runtime> for GenesisConfig<T> {
+    fn build(&self, pallet: &mut Pallet<T>, _runtime: &mut Runtime) -> DispatchResult {
+        if let Some(existential_deposit) = self.existential_deposit {
+            if existential_deposit.is_zero() {
+                return Err(DispatchError::Other(
+                    "the existential deposit can't be zero",
+                ));
+            }
+            pallet.existential_deposit = existential_deposit;
+        }
+        for (who, amount) in &self.balances {
+            if pallet.balances.contains_key(who) {
+                return Err(DispatchError::Other("account endowed twice at genesis"));
+            }
+            pallet
+                .mint(who.clone(), *amount)
+                .map_err(|error| DispatchError::Other(error.as_str()))?;
+        }
+        Ok(())
+    }
+}
+
+/// The Balances Pallet has no per-block logic.
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> Encode for Pallet<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.balances.encode_to(dest);
+        self.reserved.encode_to(dest);
+        self.locks.encode_to(dest);
+        self.total_issuance.encode_to(dest);
+        self.existential_deposit.encode_to(dest);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[derive(Debug, Clone, PartialEq)]
     struct TestConfig;
 
     impl super::Config for TestConfig {
         type Balance = u128;
+
+        const EXISTENTIAL_DEPOSIT: u128 = 2;
+
+        const BASE_FEE: u128 = 5;
+        const BYTE_FEE: u128 = 2;
+        const WEIGHT_FEE: u128 = 1;
+
+        fn fee_collector() -> Option<String> {
+            Some("collector".to_string())
+        }
+
+        fn dust_collector() -> Option<String> {
+            Some("dust".to_string())
+        }
     }
 
     impl crate::system::Config for TestConfig {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = ();
+
+        const MAX_BLOCK_WEIGHT: u64 = 100;
     }
 
     #[test]
@@ -94,7 +800,7 @@ mod tests {
 
         assert_eq!(
             balances.transfer("daniel".to_string(), "vini".to_string(), 10),
-            Err("Insufficient balance")
+            Err(Error::InsufficientBalance)
         );
 
         balances.set_balance("daniel".to_string(), 10);
@@ -105,11 +811,503 @@ mod tests {
 
         assert_eq!(balances.get_balance("daniel".to_string()), 7);
         assert_eq!(balances.get_balance("vini".to_string()), 3);
+        assert!(balances.check_total_issuance());
+        assert_eq!(
+            balances.take_events(),
+            vec![Event::Transfer {
+                from: "daniel".to_string(),
+                to: "vini".to_string(),
+                amount: 3
+            }]
+        );
 
-        balances.set_balance("vini".to_string(), u128::MAX);
+        // The total issuance can't overflow, so the balance is written directly to storage.
+        balances.balances.insert("vini".to_string(), u128::MAX);
         assert_eq!(
             balances.transfer("daniel".to_string(), "vini".to_string(), 3),
-            Err("Overflow")
+            Err(Error::Overflow)
+        );
+        assert!(balances.take_events().is_empty());
+    }
+
+    #[test]
+    fn charge_fee() {
+        let mut balances = Pallet::<TestConfig>::new();
+        assert_eq!(Pallet::<TestConfig>::compute_fee(10, 3), Some(5 + 20 + 3));
+
+        assert_eq!(
+            balances.charge_fee(&"daniel".to_string(), 10, 3),
+            Err(Error::InsufficientBalance)
+        );
+
+        balances.set_balance("daniel".to_string(), 100);
+        assert_eq!(balances.charge_fee(&"daniel".to_string(), 10, 3), Ok(28));
+        assert_eq!(balances.get_balance("daniel".to_string()), 72);
+        assert_eq!(balances.get_balance("collector".to_string()), 28);
+        assert!(balances.check_total_issuance());
+        assert_eq!(
+            balances.take_events(),
+            vec![Event::FeePaid {
+                who: "daniel".to_string(),
+                amount: 28
+            }]
+        );
+
+        // If the collector can't be credited, the fee is not withdrawn either.
+        balances.balances.insert("collector".to_string(), u128::MAX);
+        assert_eq!(
+            balances.charge_fee(&"daniel".to_string(), 10, 3),
+            Err(Error::Overflow)
+        );
+        assert_eq!(balances.get_balance("daniel".to_string()), 72);
+        assert!(balances.take_events().is_empty());
+    }
+
+    #[test]
+    fn charge_fee_to_the_dust_collector() {
+        // A single pot collects both the fees and the dust.
+        #[derive(Debug, Clone, PartialEq)]
+        struct PotConfig;
+
+        impl super::Config for PotConfig {
+            type Balance = u128;
+
+            const EXISTENTIAL_DEPOSIT: u128 = 10;
+
+            const BASE_FEE: u128 = 5;
+            const BYTE_FEE: u128 = 0;
+            const WEIGHT_FEE: u128 = 0;
+
+            fn fee_collector() -> Option<String> {
+                Some("pot".to_string())
+            }
+
+            fn dust_collector() -> Option<String> {
+                Some("pot".to_string())
+            }
+        }
+
+        impl crate::system::Config for PotConfig {
+            type AccountId = String;
+            type BlockNumber = u32;
+            type Nonce = u32;
+            type RuntimeEvent = ();
+
+            const MAX_BLOCK_WEIGHT: u64 = 100;
+        }
+
+        let mut balances = Pallet::<PotConfig>::new();
+        balances.set_balance("daniel".to_string(), 12);
+
+        // An empty pot can't be credited with less than the existential deposit.
+        assert_eq!(
+            balances.charge_fee(&"daniel".to_string(), 0, 0),
+            Err(Error::ExistentialDeposit)
+        );
+        assert_eq!(balances.get_balance("daniel".to_string()), 12);
+
+        // Paying the fee reaps the payer, and the pot receives both the fee and the dust.
+        balances.set_balance("pot".to_string(), 100);
+        assert_eq!(balances.charge_fee(&"daniel".to_string(), 0, 0), Ok(5));
+        assert_eq!(balances.get_balance("daniel".to_string()), 0);
+        assert_eq!(balances.get_balance("pot".to_string()), 112);
+        assert_eq!(balances.total_issuance(), 112);
+        assert!(balances.check_total_issuance());
+    }
+
+    #[test]
+    fn reap_accounts_below_existential_deposit() {
+        let mut balances = Pallet::<TestConfig>::new();
+        balances.set_balance("daniel".to_string(), 10);
+
+        // The receiver must end up with at least the existential deposit.
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 1),
+            Err(Error::ExistentialDeposit)
+        );
+
+        // `transfer_keep_alive` refuses to reap the caller, `transfer` reaps it.
+        assert_eq!(
+            balances.transfer_keep_alive("daniel".to_string(), "vini".to_string(), 9),
+            Err(Error::KeepAlive)
+        );
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 9),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance("daniel".to_string()), 0);
+        assert_eq!(balances.get_balance("vini".to_string()), 9);
+        assert_eq!(
+            balances.take_events()[1],
+            Event::AccountReaped {
+                who: "daniel".to_string(),
+                dust: 1
+            }
+        );
+        // The dust collector is never created below the existential deposit, so the dust is
+        // burned.
+        assert_eq!(balances.get_balance("dust".to_string()), 0);
+        assert_eq!(balances.balances.len(), 1);
+        assert_eq!(balances.total_issuance(), 9);
+
+        // Once the dust collector holds the existential deposit, it receives the dust.
+        balances.set_balance("dust".to_string(), 2);
+        balances.set_balance("daniel".to_string(), 10);
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 9),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance("dust".to_string()), 3);
+        let _ = balances.take_events();
+
+        // Transferring the whole balance reaps the account without any dust.
+        assert_eq!(
+            balances.transfer("vini".to_string(), "daniel".to_string(), 18),
+            Ok(())
+        );
+        assert_eq!(balances.balances.len(), 2);
+        assert_eq!(balances.get_balance("dust".to_string()), 3);
+        assert_eq!(balances.take_events().len(), 1);
+        assert_eq!(balances.total_issuance(), 21);
+        assert!(balances.check_total_issuance());
+    }
+
+    #[test]
+    fn mint_and_burn() {
+        let mut balances = Pallet::<TestConfig>::new();
+
+        assert_eq!(
+            balances.mint("daniel".to_string(), 1),
+            Err(Error::ExistentialDeposit)
+        );
+        assert_eq!(balances.mint("daniel".to_string(), 100), Ok(()));
+        balances.set_balance("vini".to_string(), 50);
+        assert_eq!(balances.total_issuance(), 150);
+        assert_eq!(
+            balances.mint("vini".to_string(), u128::MAX),
+            Err(Error::Overflow)
+        );
+
+        assert_eq!(
+            balances.burn("daniel".to_string(), 101),
+            Err(Error::InsufficientBalance)
+        );
+        assert_eq!(balances.burn("daniel".to_string(), 60), Ok(()));
+        assert_eq!(balances.get_balance("daniel".to_string()), 40);
+        assert_eq!(balances.total_issuance(), 90);
+
+        // Burning all but the dust reaps the account. The dust is too little to create the dust
+        // collector, so it is burned too.
+        assert_eq!(balances.burn("daniel".to_string(), 39), Ok(()));
+        assert_eq!(balances.get_balance("dust".to_string()), 0);
+        assert_eq!(balances.total_issuance(), 50);
+
+        // Setting a balance replaces the old one in the total issuance.
+        balances.set_balance("vini".to_string(), 20);
+        assert_eq!(balances.total_issuance(), 20);
+        assert!(balances.check_total_issuance());
+
+        balances.balances.insert("daniel".to_string(), 5);
+        assert!(!balances.check_total_issuance());
+    }
+
+    #[test]
+    fn genesis_config() {
+        let mut balances = Pallet::<TestConfig>::new();
+        let config = GenesisConfig::<TestConfig> {
+            balances: vec![("daniel".to_string(), 100), ("vini".to_string(), 50)],
+            existential_deposit: None,
+        };
+        assert_eq!(config.build(&mut balances, &mut ()), Ok(()));
+        assert_eq!(balances.get_balance("daniel".to_string()), 100);
+        assert_eq!(balances.get_balance("vini".to_string()), 50);
+        assert_eq!(balances.total_issuance(), 150);
+
+        let endowed_twice = GenesisConfig::<TestConfig> {
+            balances: vec![("daniel".to_string(), 100), ("daniel".to_string(), 50)],
+            existential_deposit: None,
+        };
+        assert_eq!(
+            endowed_twice.build(&mut Pallet::new(), &mut ()),
+            Err(DispatchError::Other("account endowed twice at genesis"))
+        );
+        let below_existential_deposit = GenesisConfig::<TestConfig> {
+            balances: vec![("daniel".to_string(), 1)],
+            existential_deposit: None,
+        };
+        assert_eq!(
+            below_existential_deposit.build(&mut Pallet::new(), &mut ()),
+            Err(DispatchError::Other(Error::ExistentialDeposit.as_str()))
+        );
+
+        // A chain can have its own existential deposit, which applies to the endowed accounts too.
+        let mut balances = Pallet::<TestConfig>::new();
+        let config = GenesisConfig::<TestConfig> {
+            balances: vec![("daniel".to_string(), 1)],
+            existential_deposit: Some(1),
+        };
+        assert_eq!(config.build(&mut balances, &mut ()), Ok(()));
+        assert_eq!(balances.existential_deposit(), 1);
+        assert_eq!(balances.get_balance("daniel".to_string()), 1);
+        let zero_existential_deposit = GenesisConfig::<TestConfig> {
+            balances: Vec::new(),
+            existential_deposit: Some(0),
+        };
+        assert_eq!(
+            zero_existential_deposit.build(&mut Pallet::new(), &mut ()),
+            Err(DispatchError::Other(
+                "the existential deposit can't be zero"
+            ))
+        );
+    }
+
+    #[test]
+    fn reserve_and_unreserve() {
+        let mut balances = Pallet::<TestConfig>::new();
+        balances.set_balance("daniel".to_string(), 100);
+
+        assert_eq!(
+            balances.reserve(&"daniel".to_string(), 101),
+            Err(Error::InsufficientBalance)
+        );
+        assert_eq!(balances.reserve(&"daniel".to_string(), 100), Ok(()));
+        assert_eq!(balances.get_balance("daniel".to_string()), 0);
+        assert_eq!(balances.reserved_balance(&"daniel".to_string()), 100);
+        assert_eq!(balances.total_issuance(), 100);
+
+        // The reserved balance can't be transferred, but keeps the account alive.
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 10),
+            Err(Error::InsufficientBalance)
+        );
+        assert!(balances
+            .take_events()
+            .iter()
+            .all(|event| !matches!(event, Event::AccountReaped { .. })));
+
+        assert_eq!(
+            balances.unreserve(&"daniel".to_string(), 101),
+            Err(Error::InsufficientReserved)
+        );
+        assert_eq!(balances.unreserve(&"daniel".to_string(), 99), Ok(()));
+        assert_eq!(balances.get_balance("daniel".to_string()), 99);
+        assert_eq!(balances.reserved_balance(&"daniel".to_string()), 1);
+        assert!(balances.check_total_issuance());
+
+        // A free balance below the existential deposit is fine while some balance is reserved.
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 98),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance("daniel".to_string()), 1);
+        assert_eq!(balances.unreserve(&"daniel".to_string(), 1), Ok(()));
+        assert_eq!(balances.get_balance("daniel".to_string()), 2);
+        assert!(balances.check_total_issuance());
+    }
+
+    #[test]
+    fn slash_and_repatriate_reserved() {
+        let mut balances = Pallet::<TestConfig>::new();
+        balances.set_balance("daniel".to_string(), 101);
+        assert_eq!(balances.reserve(&"daniel".to_string(), 100), Ok(()));
+
+        assert_eq!(
+            balances.slash_reserved(&"daniel".to_string(), 101),
+            Err(Error::InsufficientReserved)
+        );
+        assert_eq!(balances.slash_reserved(&"daniel".to_string(), 40), Ok(()));
+        assert_eq!(balances.reserved_balance(&"daniel".to_string()), 60);
+        assert_eq!(balances.total_issuance(), 61);
+
+        // The beneficiary of a repatriation into its free balance must be kept alive.
+        assert_eq!(
+            balances.repatriate_reserved(
+                &"daniel".to_string(),
+                &"vini".to_string(),
+                1,
+                BalanceStatus::Free
+            ),
+            Err(Error::ExistentialDeposit)
+        );
+        assert_eq!(
+            balances.repatriate_reserved(
+                &"daniel".to_string(),
+                &"vini".to_string(),
+                20,
+                BalanceStatus::Free
+            ),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance("vini".to_string()), 20);
+        assert_eq!(
+            balances.repatriate_reserved(
+                &"daniel".to_string(),
+                &"vini".to_string(),
+                30,
+                BalanceStatus::Reserved
+            ),
+            Ok(())
+        );
+        assert_eq!(balances.reserved_balance(&"vini".to_string()), 30);
+        assert_eq!(balances.total_issuance(), 61);
+        assert!(balances.check_total_issuance());
+
+        // Once its reserved balance is gone, daniel is reaped for holding less than the existential
+        // deposit.
+        assert_eq!(balances.slash_reserved(&"daniel".to_string(), 10), Ok(()));
+        assert_eq!(balances.get_balance("daniel".to_string()), 0);
+        assert_eq!(balances.get_balance("dust".to_string()), 0);
+        assert_eq!(
+            balances.take_events().last(),
+            Some(&Event::AccountReaped {
+                who: "daniel".to_string(),
+                dust: 1
+            })
+        );
+        assert_eq!(balances.total_issuance(), 50);
+        assert!(balances.check_total_issuance());
+    }
+
+    #[test]
+    fn named_locks() {
+        const VOTING: LockIdentifier = *b"voting  ";
+        const VESTING: LockIdentifier = *b"vesting ";
+
+        let mut balances = Pallet::<TestConfig>::new();
+        balances.set_balance("daniel".to_string(), 100);
+
+        // Locks overlap, so only the largest one counts.
+        balances.set_lock(VOTING, &"daniel".to_string(), 50);
+        balances.set_lock(VESTING, &"daniel".to_string(), 30);
+        assert_eq!(balances.locked_balance(&"daniel".to_string()), 50);
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 51),
+            Err(Error::LiquidityRestrictions)
+        );
+        assert_eq!(
+            balances.reserve(&"daniel".to_string(), 51),
+            Err(Error::LiquidityRestrictions)
+        );
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 50),
+            Ok(())
+        );
+
+        // Extending a lock never shrinks it.
+        balances.extend_lock(VOTING, &"daniel".to_string(), 40);
+        assert_eq!(balances.locked_balance(&"daniel".to_string()), 50);
+        balances.extend_lock(VESTING, &"daniel".to_string(), 60);
+        assert_eq!(balances.locked_balance(&"daniel".to_string()), 60);
+
+        balances.remove_lock(VESTING, &"daniel".to_string());
+        balances.set_lock(VOTING, &"daniel".to_string(), 0);
+        assert_eq!(balances.locked_balance(&"daniel".to_string()), 0);
+        assert_eq!(
+            balances.take_events().last(),
+            Some(&Event::LockRemoved {
+                who: "daniel".to_string(),
+                id: VOTING
+            })
+        );
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 50),
+            Ok(())
+        );
+        assert!(balances.locks.is_empty());
+    }
+
+    #[test]
+    fn locked_funds_are_never_withdrawn() {
+        const VOTING: LockIdentifier = *b"voting  ";
+
+        let mut balances = Pallet::<TestConfig>::new();
+        balances.set_balance("daniel".to_string(), 20);
+
+        // A fully locked balance can't be burned, nor pay a fee.
+        balances.set_lock(VOTING, &"daniel".to_string(), 20);
+        assert_eq!(
+            balances.burn("daniel".to_string(), 20),
+            Err(Error::LiquidityRestrictions)
+        );
+        assert_eq!(
+            balances.charge_fee(&"daniel".to_string(), 0, 0),
+            Err(Error::LiquidityRestrictions)
+        );
+        assert_eq!(balances.get_balance("daniel".to_string()), 20);
+
+        // Leaving only the locked funds, below the existential deposit, would reap the account and
+        // send them to the dust collector.
+        balances.set_lock(VOTING, &"daniel".to_string(), 1);
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 19),
+            Err(Error::LiquidityRestrictions)
+        );
+        assert_eq!(
+            balances.burn("daniel".to_string(), 19),
+            Err(Error::LiquidityRestrictions)
+        );
+        assert_eq!(balances.get_balance("daniel".to_string()), 20);
+        assert_eq!(balances.get_balance("dust".to_string()), 0);
+
+        // Down to the existential deposit, the locked funds stay in the account.
+        assert_eq!(
+            balances.transfer("daniel".to_string(), "vini".to_string(), 18),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance("daniel".to_string()), 2);
+        assert_eq!(balances.locked_balance(&"daniel".to_string()), 1);
+
+        // The reserve can't keep a locked account alive, as slashing it would then reap the
+        // account and send the locked funds to the dust collector.
+        balances.set_balance("daniel".to_string(), 20);
+        assert_eq!(
+            balances.reserve(&"daniel".to_string(), 19),
+            Err(Error::LiquidityRestrictions)
+        );
+        assert_eq!(balances.reserve(&"daniel".to_string(), 18), Ok(()));
+        assert_eq!(balances.slash_reserved(&"daniel".to_string(), 18), Ok(()));
+        assert_eq!(balances.get_balance("daniel".to_string()), 2);
+        assert_eq!(balances.locked_balance(&"daniel".to_string()), 1);
+        assert_eq!(balances.get_balance("dust".to_string()), 0);
+        assert!(balances.check_total_issuance());
+    }
+
+    #[test]
+    fn currency() {
+        // Pays `amount` and puts the same amount aside, knowing nothing about the Balances Pallet.
+        fn pay_and_reserve<C: Currency<String, Balance = u128>>(
+            currency: &mut C,
+            amount: u128,
+        ) -> DispatchResult<C::Error> {
+            currency.transfer(&"daniel".to_string(), &"vini".to_string(), amount)?;
+            currency.reserve(&"daniel".to_string(), amount)
+        }
+
+        let mut balances = Pallet::<TestConfig>::new();
+        assert_eq!(balances.deposit(&"daniel".to_string(), 100), Ok(()));
+        assert_eq!(pay_and_reserve(&mut balances, 30), Ok(()));
+        assert_eq!(balances.free_balance(&"daniel".to_string()), 40);
+        assert_eq!(
+            Currency::reserved_balance(&balances, &"daniel".to_string()),
+            30
+        );
+        assert_eq!(
+            pay_and_reserve(&mut balances, 50),
+            Err(Error::InsufficientBalance)
+        );
+
+        assert_eq!(
+            Currency::unreserve(&mut balances, &"daniel".to_string(), 30),
+            Ok(())
+        );
+        // Locked funds can't be withdrawn through the currency either.
+        balances.set_lock(*b"voting  ", &"daniel".to_string(), 10);
+        assert_eq!(
+            balances.withdraw(&"daniel".to_string(), 61),
+            Err(Error::LiquidityRestrictions)
         );
+        assert_eq!(balances.withdraw(&"daniel".to_string(), 60), Ok(()));
+        assert_eq!(balances.total_issuance(), 40);
+        assert!(balances.check_total_issuance());
     }
 }
@@ -0,0 +1,250 @@
+use crate::crypto::{AccountId32, Keyring};
+use crate::support::{DispatchError, Weight};
+use crate::{balances, proof_of_existence, system, types, Runtime, RuntimeGenesisConfig};
+use core::fmt;
+use serde::Deserialize;
+use std::path::Path;
+
+/// The definition of a chain in a JSON file, so new chains can be started without recompiling.
+///
+/// Accounts are written either as the hex of their 32 bytes, like `0x1234...`, or as the name of
+/// a development account, like `//Alice`. The content of claims is written as the hex of its hash.
+///
+/// ```json
+/// {
+///   "name": "Development",
+///   "id": "dev",
+///   "parameters": { "existential_deposit": 10 },
+///   "genesis": {
+///     "balances": [["//Alice", 10000]],
+///     "claims": [["//Alice", "0xaaa3d70d66a19ed24603c8922081d04fb6031484558b28e2b8f7b5f0067c85c5"]]
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainSpec {
+    /// A human readable name for the chain.
+    pub name: String,
+    /// A short identifier for the chain, like `dev` or `staging`.
+    pub id: String,
+    #[serde(default)]
+    pub parameters: Parameters,
+    #[serde(default)]
+    pub genesis: Genesis,
+}
+
+/// The parameters of the runtime for a chain. They are part of its genesis state, and those which
+/// are not given keep the default declared by the `Config` of the runtime.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub existential_deposit: Option<types::Balance>,
+    pub claim_deposit: Option<types::Balance>,
+    pub max_claims_per_account: Option<u32>,
+    pub max_block_weight: Option<Weight>,
+}
+
+/// The initial state of the chain, see `RuntimeGenesisConfig`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Genesis {
+    /// The accounts endowed with some balance, and their balance.
+    #[serde(default)]
+    pub balances: Vec<(String, types::Balance)>,
+    /// The owner and content of the claims which exist from the start of the chain.
+    #[serde(default)]
+    pub claims: Vec<(String, String)>,
+}
+
+/// The reasons a chain spec can't be loaded.
+#[derive(Debug)]
+pub enum Error {
+    /// The file of the chain spec can't be read.
+    Io(std::io::Error),
+    /// The chain spec is not valid JSON, or does not have the expected fields.
+    Json(serde_json::Error),
+    /// An account is neither the hex of 32 bytes nor the name of a development account.
+    InvalidAccount(String),
+    /// The content of a claim is not the hex of a hash.
+    InvalidContent(String),
+    /// The genesis config is rejected by the runtime.
+    Genesis(DispatchError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "cannot read the chain spec: {}", error),
+            Error::Json(error) => write!(f, "invalid chain spec: {}", error),
+            Error::InvalidAccount(account) => write!(f, "invalid account: {}", account),
+            Error::InvalidContent(content) => write!(f, "invalid claim content: {}", content),
+            Error::Genesis(error) => write!(f, "invalid genesis: {:?}", error),
+        }
+    }
+}
+
+impl ChainSpec {
+    /// Parse a chain spec from its JSON.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(Error::Json)
+    }
+
+    /// Read the chain spec in the JSON file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_json(&std::fs::read_to_string(path).map_err(Error::Io)?)
+    }
+
+    /// The genesis config of the runtime for this chain.
+    pub fn genesis_config(&self) -> Result<RuntimeGenesisConfig, Error> {
+        let parameters = &self.parameters;
+        let balances = self
+            .genesis
+            .balances
+            .iter()
+            .map(|(account, balance)| Ok((parse_account(account)?, *balance)))
+            .collect::<Result<_, Error>>()?;
+        let claims = self
+            .genesis
+            .claims
+            .iter()
+            .map(|(owner, content)| Ok((parse_account(owner)?, parse_content(content)?)))
+            .collect::<Result<_, Error>>()?;
+        Ok(RuntimeGenesisConfig {
+            system: system::GenesisConfig {
+                max_block_weight: parameters.max_block_weight,
+            },
+            balances: balances::GenesisConfig {
+                balances,
+                existential_deposit: parameters.existential_deposit,
+            },
+            proof_of_existence: proof_of_existence::GenesisConfig {
+                claims,
+                claim_deposit: parameters.claim_deposit,
+                max_claims_per_account: parameters.max_claims_per_account,
+            },
+        })
+    }
+
+    /// Start the chain, by building the genesis state of the runtime.
+    pub fn build(&self) -> Result<Runtime, Error> {
+        Runtime::from_genesis(self.genesis_config()?).map_err(Error::Genesis)
+    }
+}
+
+/// Parse the hex of `N` bytes, with a `0x` prefix.
+fn parse_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let hex = hex.strip_prefix("0x")?;
+    if hex.len() != 2 * N || !hex.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+fn parse_account(account: &str) -> Result<AccountId32, Error> {
+    Keyring::from_path(account)
+        .map(Keyring::to_account_id)
+        .or_else(|| parse_hex(account).map(AccountId32))
+        .ok_or_else(|| Error::InvalidAccount(account.to_string()))
+}
+
+fn parse_content(content: &str) -> Result<types::Content, Error> {
+    parse_hex(content).ok_or_else(|| Error::InvalidContent(content.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChainSpec, Error};
+    use crate::crypto::Keyring;
+    use crate::support::hash;
+
+    #[test]
+    fn build_chains_from_their_spec() {
+        for path in ["chain_specs/dev.json", "chain_specs/local.json"] {
+            let spec = ChainSpec::from_file(path).unwrap();
+            let runtime = spec.build().unwrap();
+            assert_eq!(
+                runtime.balances.get_balance(Keyring::Alice.to_account_id()),
+                10_000 - 100
+            );
+            assert!(runtime
+                .proof_of_existence
+                .get_claim(&hash(b"genesis_document"))
+                .is_some());
+        }
+
+        let spec = ChainSpec::from_json(&format!(
+            r#"{{"name": "Test", "id": "test", "genesis": {{"balances": [["{:?}", 100]]}}}}"#,
+            Keyring::Bob.to_account_id()
+        ))
+        .unwrap();
+        let runtime = spec.build().unwrap();
+        assert_eq!(
+            runtime.balances.get_balance(Keyring::Bob.to_account_id()),
+            100
+        );
+    }
+
+    #[test]
+    fn chains_have_their_own_parameters() {
+        let runtime = ChainSpec::from_file("chain_specs/staging.json")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(runtime.balances.existential_deposit(), 100);
+        assert_eq!(runtime.proof_of_existence.claim_deposit(), 500);
+        assert_eq!(runtime.proof_of_existence.max_claims_per_account(), 4);
+        assert_eq!(runtime.system.max_block_weight(), 200);
+        // The genesis claims reserve the deposit of the chain.
+        assert_eq!(
+            runtime.balances.get_balance(Keyring::Alice.to_account_id()),
+            100_000 - 500
+        );
+
+        // Parameters which are not given keep the defaults of the runtime, and differ from the
+        // genesis state of another chain.
+        let local = ChainSpec::from_file("chain_specs/local.json")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(local.balances.existential_deposit(), 10);
+        assert_ne!(local.system.genesis_hash(), runtime.system.genesis_hash());
+    }
+
+    #[test]
+    fn reject_invalid_specs() {
+        let error = |json: &str| {
+            ChainSpec::from_json(json)
+                .and_then(|spec| spec.build())
+                .unwrap_err()
+        };
+
+        assert!(matches!(error("{}"), Error::Json(_)));
+        assert!(matches!(
+            error(r#"{"name": "Test", "id": "test", "unknown": 1}"#),
+            Error::Json(_)
+        ));
+        assert!(matches!(
+            error(r#"{"name": "Test", "id": "test", "genesis": {"balances": [["//Dave", 100]]}}"#),
+            Error::InvalidAccount(account) if account == "//Dave"
+        ));
+        assert!(matches!(
+            error(
+                r#"{"name": "Test", "id": "test", "genesis": {"claims": [["//Alice", "0x12"]]}}"#
+            ),
+            Error::InvalidContent(_)
+        ));
+        assert!(matches!(
+            error(r#"{"name": "Test", "id": "test", "parameters": {"existential_deposit": 0}}"#),
+            Error::Genesis(_)
+        ));
+        assert!(matches!(
+            error(r#"{"name": "Test", "id": "test", "genesis": {"balances": [["//Alice", 1]]}}"#),
+            Error::Genesis(_)
+        ));
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A deterministic binary encoding, loosely based on the SCALE codec used by the Polkadot SDK.
+///
+/// - Integers are encoded as fixed width little endian bytes.
+/// - Sequences (`Vec`, `String`, `BTreeMap`, `BTreeSet`) are prefixed with their length as a
+///   compact integer, see `encode_compact`.
+/// - Enums are encoded as the index of the variant as a `u8`, followed by its fields.
+/// - Structs and tuples are the concatenation of their fields.
+///
+/// Since there is only one way to encode a value, hashing the encoded bytes is a safe way to
+/// commit to a value, for example in the roots of a block header. Values can be decoded back with
+/// `Decode`.
+pub trait Encode {
+    /// Append the encoding of `self` to `dest`.
+    fn encode_to(&self, dest: &mut Vec<u8>);
+
+    /// Return the encoding of `self`.
+    fn encode(&self) -> Vec<u8> {
+        let mut dest = Vec::new();
+        self.encode_to(&mut dest);
+        dest
+    }
+}
+
+/// The inverse of `Encode`. Decoding only accepts the one encoding `Encode` would produce, so
+/// decoding then encoding some bytes gives back the same bytes.
+pub trait Decode: Sized {
+    /// Decode a value from the start of `input`, advancing `input` past the decoded bytes.
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error>;
+
+    /// Decode a value from `input`, which must hold nothing else.
+    fn decode(mut input: &[u8]) -> Result<Self, Error> {
+        let value = Self::decode_from(&mut input)?;
+        if !input.is_empty() {
+            return Err(Error::TrailingBytes);
+        }
+        Ok(value)
+    }
+}
+
+/// The reasons some bytes can't be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The input ended before the value was fully decoded.
+    UnexpectedEnd,
+    /// The input has bytes left after the value, see `Decode::decode`.
+    TrailingBytes,
+    /// The input is not the encoding of any value, for example because it has an unknown enum
+    /// variant. The message describes what is wrong.
+    Invalid(&'static str),
+}
+
+/// Split off the first `len` bytes of `input`.
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if input.len() < len {
+        return Err(Error::UnexpectedEnd);
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+macro_rules! impl_encode_for_int {
+    ($( $t:ty ),*) => {
+        $(
+            impl Encode for $t {
+                fn encode_to(&self, dest: &mut Vec<u8>) {
+                    dest.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+
+            impl Decode for $t {
+                fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+                    let bytes = take(input, core::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_le_bytes(bytes.try_into().expect("the size of the integer")))
+                }
+            }
+        )*
+    };
+}
+
+impl_encode_for_int!(u8, u16, u32, u64, u128);
+
+impl Encode for bool {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(*self as u8);
+    }
+}
+
+impl Decode for bool {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        match u8::decode_from(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::Invalid("invalid bool")),
+        }
+    }
+}
+
+impl Encode for () {
+    fn encode_to(&self, _dest: &mut Vec<u8>) {}
+}
+
+impl Decode for () {
+    fn decode_from(_input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(())
+    }
+}
+
+/// Encode `value` as a compact integer, which takes fewer bytes for smaller values, like the
+/// compact integers of SCALE. The two lowest bits of the first byte give the mode:
+///
+/// - `0b00`: a single byte, for values below `2^6`.
+/// - `0b01`: two bytes, for values below `2^14`.
+/// - `0b10`: four bytes, for values below `2^30`.
+/// - `0b11`: the upper six bits hold the number of bytes which follow minus 4, and the value
+///   follows as little endian bytes.
+///
+/// The value is stored in the remaining bits of the little endian bytes, and always in the
+/// smallest mode which fits it.
+fn encode_compact(value: u64, dest: &mut Vec<u8>) {
+    match value {
+        0..=0x3f => dest.push((value as u8) << 2),
+        0x40..=0x3fff => dest.extend_from_slice(&(((value as u16) << 2) | 0b01).to_le_bytes()),
+        0x4000..=0x3fff_ffff => {
+            dest.extend_from_slice(&(((value as u32) << 2) | 0b10).to_le_bytes())
+        }
+        _ => {
+            let len = 8 - value.leading_zeros() as usize / 8;
+            dest.push((((len - 4) as u8) << 2) | 0b11);
+            dest.extend_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+/// Decode a compact integer, see `encode_compact`.
+fn decode_compact(input: &mut &[u8]) -> Result<u64, Error> {
+    let first = u8::decode_from(input)?;
+    let (value, min) = match first & 0b11 {
+        0b00 => return Ok(u64::from(first >> 2)),
+        0b01 => {
+            let bytes = [first, u8::decode_from(input)?];
+            (u64::from(u16::from_le_bytes(bytes) >> 2), 0x40)
+        }
+        0b10 => {
+            let mut bytes = [first, 0, 0, 0];
+            bytes[1..].copy_from_slice(take(input, 3)?);
+            (u64::from(u32::from_le_bytes(bytes) >> 2), 0x4000)
+        }
+        _ => {
+            let len = usize::from(first >> 2) + 4;
+            if len > 8 {
+                return Err(Error::Invalid("compact integer out of range"));
+            }
+            let mut bytes = [0; 8];
+            bytes[..len].copy_from_slice(take(input, len)?);
+            let value = u64::from_le_bytes(bytes);
+            if bytes[len - 1] == 0 {
+                return Err(Error::Invalid(
+                    "compact integer is not in its smallest mode",
+                ));
+            }
+            (value, 0x4000_0000)
+        }
+    };
+    if value < min {
+        return Err(Error::Invalid(
+            "compact integer is not in its smallest mode",
+        ));
+    }
+    Ok(value)
+}
+
+/// Encode the length prefix of a sequence.
+fn encode_len(len: usize, dest: &mut Vec<u8>) {
+    encode_compact(len as u64, dest);
+}
+
+/// Decode the length prefix of a sequence.
+fn decode_len(input: &mut &[u8]) -> Result<usize, Error> {
+    usize::try_from(decode_compact(input)?).map_err(|_| Error::Invalid("length out of range"))
+}
+
+/// Decode the `len` items of a sequence. The capacity is bounded by the size of the input, so a
+/// bogus length can't allocate a lot of memory.
+fn decode_items<T: Decode>(input: &mut &[u8], len: usize) -> Result<Vec<T>, Error> {
+    let mut items = Vec::with_capacity(len.min(input.len()));
+    for _ in 0..len {
+        items.push(T::decode_from(input)?);
+    }
+    Ok(items)
+}
+
+impl Encode for str {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        encode_len(self.len(), dest);
+        dest.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_str().encode_to(dest);
+    }
+}
+
+impl Decode for String {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        let len = decode_len(input)?;
+        let bytes = take(input, len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::Invalid("invalid utf-8"))
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (**self).encode_to(dest);
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        encode_len(self.len(), dest);
+        for item in self {
+            item.encode_to(dest);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_slice().encode_to(dest);
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        let len = decode_len(input)?;
+        decode_items(input, len)
+    }
+}
+
+/// Fixed size arrays have a known length, so no length prefix is needed.
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        for item in self {
+            item.encode_to(dest);
+        }
+    }
+}
+
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        let items = decode_items(input, N)?;
+        Ok(items
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("exactly N items are decoded")))
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        match self {
+            None => dest.push(0),
+            Some(value) => {
+                dest.push(1);
+                value.encode_to(dest);
+            }
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        match u8::decode_from(input)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode_from(input)?)),
+            _ => Err(Error::Invalid("invalid option")),
+        }
+    }
+}
+
+/// A `BTreeMap` is always iterated in order of its keys, which keeps the encoding deterministic.
+impl<K: Encode, V: Encode> Encode for BTreeMap<K, V> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        encode_len(self.len(), dest);
+        for (key, value) in self {
+            key.encode_to(dest);
+            value.encode_to(dest);
+        }
+    }
+}
+
+/// The keys must be in strictly increasing order, as they are encoded.
+impl<K: Decode + Ord, V: Decode> Decode for BTreeMap<K, V> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        let len = decode_len(input)?;
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            let (key, value) = <(K, V)>::decode_from(input)?;
+            if map.last_key_value().is_some_and(|(last, _)| *last >= key) {
+                return Err(Error::Invalid("map keys are not in increasing order"));
+            }
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+/// A `BTreeSet` is always iterated in order, which keeps the encoding deterministic.
+impl<T: Encode> Encode for BTreeSet<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        encode_len(self.len(), dest);
+        for value in self {
+            value.encode_to(dest);
+        }
+    }
+}
+
+/// The values must be in strictly increasing order, as they are encoded.
+impl<T: Decode + Ord> Decode for BTreeSet<T> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        let len = decode_len(input)?;
+        let mut set = BTreeSet::new();
+        for _ in 0..len {
+            let value = T::decode_from(input)?;
+            if set.last().is_some_and(|last| *last >= value) {
+                return Err(Error::Invalid("set values are not in increasing order"));
+            }
+            set.insert(value);
+        }
+        Ok(set)
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+        self.1.encode_to(dest);
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok((A::decode_from(input)?, B::decode_from(input)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_compact, encode_compact, Decode, Encode, Error};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[test]
+    fn encode_primitives() {
+        assert_eq!(1u32.encode(), vec![1, 0, 0, 0]);
+        assert_eq!(true.encode(), vec![1]);
+        assert_eq!("hi".encode(), vec![8, b'h', b'i']);
+        assert_eq!(vec![1u8, 2].encode(), vec![8, 1, 2]);
+        assert_eq!([1u8, 2].encode(), vec![1, 2]);
+        assert_eq!(Some(7u8).encode(), vec![1, 7]);
+        assert_eq!(None::<u8>.encode(), vec![0]);
+    }
+
+    #[test]
+    fn encode_map_is_ordered() {
+        let mut map = BTreeMap::new();
+        map.insert(2u8, 20u8);
+        map.insert(1u8, 10u8);
+        assert_eq!(map.encode(), vec![8, 1, 10, 2, 20]);
+
+        let set = BTreeSet::from([2u8, 1u8]);
+        assert_eq!(set.encode(), vec![8, 1, 2]);
+    }
+
+    #[test]
+    fn compact_integers() {
+        let cases: [(u64, &[u8]); 8] = [
+            (0, &[0]),
+            (63, &[0xfc]),
+            (64, &[0x01, 0x01]),
+            (0x3fff, &[0xfd, 0xff]),
+            (0x4000, &[0x02, 0x00, 0x01, 0x00]),
+            (0x3fff_ffff, &[0xfe, 0xff, 0xff, 0xff]),
+            (0x4000_0000, &[0x03, 0x00, 0x00, 0x00, 0x40]),
+            (
+                u64::MAX,
+                &[0x13, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+        ];
+        for (value, encoded) in cases {
+            let mut dest = Vec::new();
+            encode_compact(value, &mut dest);
+            assert_eq!(dest, encoded);
+            let mut input = encoded;
+            assert_eq!(decode_compact(&mut input), Ok(value));
+            assert!(input.is_empty());
+        }
+
+        // Every value has a single encoding, in the smallest mode which fits it.
+        for encoded in [
+            &[0x01, 0x00][..],
+            &[0x02, 0x00, 0x00, 0x00],
+            &[0x07, 0, 0, 0, 0, 0],
+        ] {
+            assert_eq!(
+                decode_compact(&mut &encoded[..]),
+                Err(Error::Invalid(
+                    "compact integer is not in its smallest mode"
+                ))
+            );
+        }
+        assert!(decode_compact(&mut &[0x17][..]).is_err());
+    }
+
+    #[test]
+    fn decode_what_is_encoded() {
+        fn roundtrip<T: Encode + Decode + PartialEq + std::fmt::Debug>(value: T) {
+            assert_eq!(T::decode(&value.encode()), Ok(value));
+        }
+
+        roundtrip(u128::MAX);
+        roundtrip(false);
+        roundtrip(());
+        roundtrip("hello".to_string());
+        roundtrip(vec![1u16; 100]);
+        roundtrip([7u8; 32]);
+        roundtrip(Some((1u8, "a".to_string())));
+        roundtrip(BTreeMap::from([(1u32, vec![true]), (5, vec![])]));
+        roundtrip(BTreeSet::from([3u64, 1, 2]));
+    }
+
+    #[test]
+    fn reject_invalid_encodings() {
+        assert_eq!(u32::decode(&[1, 0, 0]), Err(Error::UnexpectedEnd));
+        assert_eq!(u8::decode(&[1, 0]), Err(Error::TrailingBytes));
+        assert_eq!(bool::decode(&[2]), Err(Error::Invalid("invalid bool")));
+        assert_eq!(
+            Option::<u8>::decode(&[2, 0]),
+            Err(Error::Invalid("invalid option"))
+        );
+        assert_eq!(
+            String::decode(&[4, 0xff]),
+            Err(Error::Invalid("invalid utf-8"))
+        );
+        // A huge length is not trusted before the items are actually there.
+        assert_eq!(
+            Vec::<u8>::decode(&[0x03, 0xff, 0xff, 0xff, 0xff]),
+            Err(Error::UnexpectedEnd)
+        );
+        assert_eq!(
+            BTreeMap::<u8, u8>::decode(&[8, 2, 20, 1, 10]),
+            Err(Error::Invalid("map keys are not in increasing order"))
+        );
+        assert_eq!(
+            BTreeSet::<u8>::decode(&[8, 1, 1]),
+            Err(Error::Invalid("set values are not in increasing order"))
+        );
+    }
+}
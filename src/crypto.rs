@@ -0,0 +1,163 @@
+use crate::codec::{Decode, Encode, Error};
+use crate::support::hash;
+use core::fmt;
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+
+/// An ed25519 public key, identifying who signed an extrinsic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Public(pub [u8; 32]);
+
+/// An ed25519 signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature(pub [u8; 64]);
+
+impl Signature {
+    /// Check this is a valid signature of `message` by the owner of `public`.
+    pub fn verify(&self, message: &[u8], public: &Public) -> bool {
+        let Ok(key) = VerifyingKey::from_bytes(&public.0) else {
+            return false;
+        };
+        let signature = ed25519_dalek::Signature::from_bytes(&self.0);
+        key.verify(message, &signature).is_ok()
+    }
+}
+
+/// An ed25519 key pair, used to sign extrinsics.
+#[derive(Clone)]
+pub struct Pair(SigningKey);
+
+impl Pair {
+    /// Derive a key pair from a 32 bytes secret seed.
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Pair(SigningKey::from_bytes(seed))
+    }
+
+    pub fn public(&self) -> Public {
+        Public(self.0.verifying_key().to_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature(self.0.sign(message).to_bytes())
+    }
+}
+
+/// An account identifier made of 32 bytes, which is derived from the public key of the account.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AccountId32(pub [u8; 32]);
+
+impl From<Public> for AccountId32 {
+    fn from(public: Public) -> Self {
+        AccountId32(public.0)
+    }
+}
+
+/// Account ids are printed as hex, which is much easier to read than a list of bytes.
+impl fmt::Debug for AccountId32 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x")?;
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl Encode for Public {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+    }
+}
+
+impl Decode for Public {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Public(Decode::decode_from(input)?))
+    }
+}
+
+impl Encode for Signature {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+    }
+}
+
+impl Decode for Signature {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Signature(Decode::decode_from(input)?))
+    }
+}
+
+impl Encode for AccountId32 {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+    }
+}
+
+impl Decode for AccountId32 {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(AccountId32(Decode::decode_from(input)?))
+    }
+}
+
+/// Well known development accounts, whose secret seeds are derived from their names. Never use
+/// them for anything other than testing!
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyring {
+    Alice,
+    Bob,
+    Charlie,
+}
+
+impl Keyring {
+    pub const ALL: [Keyring; 3] = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Keyring::Alice => "Alice",
+            Keyring::Bob => "Bob",
+            Keyring::Charlie => "Charlie",
+        }
+    }
+
+    /// The development account with the given path, like `//Alice`.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let name = path.strip_prefix("//")?;
+        Self::ALL.into_iter().find(|keyring| keyring.name() == name)
+    }
+
+    pub fn pair(self) -> Pair {
+        Pair::from_seed(&hash(format!("//{}", self.name()).as_bytes()))
+    }
+
+    pub fn public(self) -> Public {
+        self.pair().public()
+    }
+
+    pub fn to_account_id(self) -> AccountId32 {
+        self.public().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keyring;
+
+    #[test]
+    fn sign_and_verify() {
+        let alice = Keyring::Alice.pair();
+        let signature = alice.sign(b"hello");
+
+        assert!(signature.verify(b"hello", &alice.public()));
+        assert!(!signature.verify(b"goodbye", &alice.public()));
+        assert!(!signature.verify(b"hello", &Keyring::Bob.public()));
+    }
+
+    #[test]
+    fn keyring_accounts_are_distinct() {
+        assert_ne!(Keyring::Alice.to_account_id(), Keyring::Bob.to_account_id());
+        assert_ne!(
+            Keyring::Bob.to_account_id(),
+            Keyring::Charlie.to_account_id()
+        );
+        assert_eq!(Keyring::Alice.public(), Keyring::Alice.public());
+    }
+}
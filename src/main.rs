@@ -1,30 +1,39 @@
 use crate::balances::Call::transfer;
-use proof_of_existence::Call::{create_claim, revoke_claim};
-use support::Dispatch;
+use chain_spec::ChainSpec;
+use codec::{Decode, Encode};
+use crypto::Keyring;
+use proof_of_existence::Call::{create_claim, create_merkle_claim, revoke_claim};
+use support::{Currency, Dispatch};
 
 mod balances;
+mod chain_spec;
+mod codec;
+mod crypto;
+mod merkle;
 mod proof_of_existence;
 mod support;
 mod system;
 
 mod types {
     pub type Balance = u128;
-    pub type AccountId = String;
+    pub type AccountId = crate::crypto::AccountId32;
     pub type BlockNumber = u32;
     pub type Nonce = u32;
 
-    pub type Extrinsic = crate::support::Extrinsic<AccountId, crate::RuntimeCall>;
+    pub type Extrinsic = crate::support::Extrinsic<Nonce, crate::RuntimeCall>;
     pub type Header = crate::support::Header<BlockNumber>;
     pub type Block = crate::support::Block<Header, Extrinsic>;
 
-    pub type Content = String; // TODO: no roteiro seria &'static str; mas o correto mesmo aqui deveria ser um hash
+    pub type Content = crate::support::Hash;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[macros::runtime]
 pub struct Runtime {
     system: system::Pallet<Self>,
+    #[pallet_index(1)]
     balances: balances::Pallet<Self>,
+    #[pallet_index(2)]
     proof_of_existence: proof_of_existence::Pallet<Self>,
 }
 
@@ -32,76 +41,838 @@ impl system::Config for Runtime {
     type AccountId = types::AccountId;
     type BlockNumber = types::BlockNumber;
     type Nonce = types::Nonce;
+    type RuntimeEvent = RuntimeEvent;
+
+    const MAX_BLOCK_WEIGHT: support::Weight = 100;
+
+    // Fees are paid with the Balances Pallet.
+    fn charge_transaction_fee(
+        &mut self,
+        who: &Self::AccountId,
+        len: u64,
+        weight: support::Weight,
+    ) -> support::DispatchResult {
+        self.balances
+            .charge_fee(who, len, weight)
+            .map(|_| ())
+            .map_err(|error| support::DispatchError::Other(support::PalletError::as_str(&error)))
+    }
 }
 
 impl balances::Config for Runtime {
     type Balance = types::Balance;
+
+    const EXISTENTIAL_DEPOSIT: Self::Balance = 10;
+
+    const BASE_FEE: Self::Balance = 1;
+    const BYTE_FEE: Self::Balance = 1;
+    const WEIGHT_FEE: Self::Balance = 1;
+
+    // Fees and dust are burned.
+    fn fee_collector() -> Option<Self::AccountId> {
+        None
+    }
+
+    fn dust_collector() -> Option<Self::AccountId> {
+        None
+    }
 }
 
 impl proof_of_existence::Config for Runtime {
     type Content = types::Content;
+
+    fn hash_content(data: &[u8]) -> Self::Content {
+        support::hash(data)
+    }
+
+    fn block_number(&self) -> Self::BlockNumber {
+        self.system.block_number()
+    }
+
+    // The runtime has no source of time yet, so claims only record their block number.
+    fn timestamp(&self) -> Option<u64> {
+        None
+    }
+
+    type Currency = balances::Pallet<Self>;
+    const CLAIM_DEPOSIT: types::Balance = 100;
+    const MAX_CLAIMS_PER_ACCOUNT: u32 = 16;
+
+    fn currency(&mut self) -> &mut Self::Currency {
+        &mut self.balances
+    }
+}
+
+/// Print the events emitted by the last executed block.
+fn print_events(runtime: &Runtime) {
+    println!("Events of block {}:", runtime.system.block_number());
+    for record in runtime.system.events() {
+        println!("\t{:?}: {:?}", record.phase, record.event);
+    }
+}
+
+/// Check the files at `paths` against the claims.
+fn verify_files(runtime: &Runtime, paths: impl IntoIterator<Item = String>) {
+    for path in paths {
+        match runtime.proof_of_existence.verify_file(&path) {
+            Ok(Some(claim)) => println!(
+                "{} is claimed by {:?} since block {}",
+                path, claim.owner, claim.block_number
+            ),
+            Ok(None) => println!("{} is not claimed", path),
+            Err(error) => println!("cannot read {}: {}", path, error),
+        }
+    }
 }
 
 fn main() {
-    let mut runtime = Runtime::new();
-    let alice = "alice".to_string();
-    let bob = "bob".to_string();
-    let charlie = "charlie".to_string();
-
-    runtime.balances.set_balance(alice.clone(), 100);
-
-    let block_1 = types::Block {
-        header: support::Header { block_number: 1 },
-        extrinsics: vec![
-            support::Extrinsic {
-                caller: alice.clone(),
-                call: RuntimeCall::balances(transfer {
-                    to: bob.clone(),
-                    amount: 30,
+    // `--chain <path>` starts the chain defined by a chain spec, and checks the files given after
+    // it against its genesis claims. Otherwise, we run a demo on the development chain.
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if_eq("--chain").is_some() {
+        let path = args.next().expect("missing the path of the chain spec!");
+        let runtime = ChainSpec::from_file(&path)
+            .and_then(|spec| {
+                println!("Starting {} ({})", spec.name, spec.id);
+                spec.build()
+            })
+            .unwrap_or_else(|error| panic!("cannot start the chain: {}", error));
+        println!("Genesis hash: {:?}", runtime.system.genesis_hash());
+        println!("Total issuance: {}", runtime.balances.total_issuance());
+        println!(
+            "Existential deposit: {}",
+            runtime.balances.existential_deposit()
+        );
+        println!(
+            "Claim deposit: {}, at most {} claims per account",
+            runtime.proof_of_existence.claim_deposit(),
+            runtime.proof_of_existence.max_claims_per_account()
+        );
+        println!("Max block weight: {}", runtime.system.max_block_weight());
+        verify_files(&runtime, args);
+        return;
+    }
+
+    let mut runtime = ChainSpec::from_json(include_str!("../chain_specs/dev.json"))
+        .and_then(|spec| spec.build())
+        .expect("invalid development chain spec!");
+    let genesis_hash = runtime.system.genesis_hash();
+    let alice = Keyring::Alice.pair();
+    let bob = Keyring::Bob.pair();
+    let charlie = Keyring::Charlie.to_account_id();
+
+    let block_1 = runtime
+        .build_block(vec![
+            support::Extrinsic::new_signed(
+                &alice,
+                0,
+                RuntimeCall::balances(transfer {
+                    to: Keyring::Bob.to_account_id(),
+                    amount: 3_000,
                 }),
-            },
-            support::Extrinsic {
-                caller: alice.clone(),
-                call: RuntimeCall::balances(transfer {
+                genesis_hash,
+            ),
+            support::Extrinsic::new_signed(
+                &alice,
+                1,
+                RuntimeCall::balances(transfer {
                     to: charlie,
-                    amount: 20,
+                    amount: 2_000,
                 }),
-            },
-        ],
-    };
-
-    let block_2 = types::Block {
-        header: support::Header { block_number: 2 },
-        extrinsics: vec![
-            support::Extrinsic {
-                caller: alice.clone(),
-                call: RuntimeCall::proof_of_existence(create_claim {
-                    claim: "my_document".to_string(),
+                genesis_hash,
+            ),
+        ])
+        .expect("invalid extrinsics!");
+    runtime.execute_block(block_1).expect("invalid block!");
+    print_events(&runtime);
+
+    // Bob claims all of these documents at once, by claiming their Merkle root.
+    let documents = [&b"contract"[..], b"invoice", b"receipt"].map(support::hash);
+    let block_2 = runtime
+        .build_block(vec![
+            support::Extrinsic::new_signed(
+                &alice,
+                2,
+                RuntimeCall::proof_of_existence(create_claim {
+                    claim: support::hash(b"my_document"),
+                    lifetime: None,
                 }),
-            },
-            support::Extrinsic {
-                caller: bob.clone(),
-                call: RuntimeCall::proof_of_existence(create_claim {
-                    claim: "my_document2".to_string(),
+                genesis_hash,
+            ),
+            support::Extrinsic::new_signed(
+                &bob,
+                0,
+                RuntimeCall::proof_of_existence(create_claim {
+                    claim: support::hash(b"my_document2"),
+                    lifetime: None,
                 }),
-            },
-            support::Extrinsic {
-                caller: alice.clone(),
-                call: RuntimeCall::proof_of_existence(revoke_claim {
-                    claim: "my_document".to_string(),
+                genesis_hash,
+            ),
+            support::Extrinsic::new_signed(
+                &alice,
+                3,
+                RuntimeCall::proof_of_existence(revoke_claim {
+                    claim: support::hash(b"my_document"),
                 }),
-            },
-        ],
-    };
-
-    runtime.execute_block(block_1).expect("invalid block!");
+                genesis_hash,
+            ),
+            support::Extrinsic::new_signed(
+                &bob,
+                1,
+                RuntimeCall::proof_of_existence(create_merkle_claim {
+                    root: proof_of_existence::Pallet::<Runtime>::merkle_root(&documents)
+                        .expect("no documents!"),
+                    lifetime: None,
+                }),
+                genesis_hash,
+            ),
+        ])
+        .expect("invalid extrinsics!");
+    // Blocks are sent to other nodes as bytes, which they decode before executing the block.
+    let bytes = block_2.encode();
+    println!("Block 2 is {} bytes", bytes.len());
+    let block_2 = types::Block::decode(&bytes).expect("cannot decode the block!");
     runtime.execute_block(block_2).expect("invalid block!");
+    print_events(&runtime);
 
     assert_eq!(
         runtime
             .proof_of_existence
-            .get_claim(&"my_document2".to_string()),
-        Some(&bob)
+            .get_claim(&support::hash(b"my_document2"))
+            .map(|claim| (claim.owner, claim.block_number)),
+        Some((Keyring::Bob.to_account_id(), 2))
+    );
+    // Any of the documents can be proven on its own against the claimed root.
+    let proof = proof_of_existence::Pallet::<Runtime>::merkle_proof(&documents, 1)
+        .expect("no such document!");
+    assert!(runtime
+        .proof_of_existence
+        .verify_merkle_claim(&documents[1], &proof)
+        .is_some());
+
+    let bob_account = Keyring::Bob.to_account_id();
+    assert_eq!(runtime.proof_of_existence.claim_count(&bob_account), 2);
+    for (content, claim) in runtime.proof_of_existence.claims_of(&bob_account, None, 10) {
+        println!("Bob claimed {content:?} in block {}", claim.block_number);
+    }
+
+    // Other pallets reach the balances through the `Currency` trait. Reserved balances can't be
+    // transferred, until they are unreserved, slashed or repatriated.
+    let currency = &mut runtime.balances;
+    Currency::transfer(currency, &bob_account, &charlie, 100).expect("cannot transfer!");
+    Currency::reserve(currency, &bob_account, 1_000).expect("cannot reserve!");
+    currency
+        .repatriate_reserved(&bob_account, &charlie, 500, balances::BalanceStatus::Free)
+        .expect("cannot repatriate!");
+    currency
+        .slash_reserved(&bob_account, 200)
+        .expect("cannot slash!");
+    Currency::unreserve(currency, &bob_account, 300).expect("cannot unreserve!");
+    currency.withdraw(&charlie, 100).expect("cannot burn!");
+    println!(
+        "Bob has {} free and {} reserved",
+        currency.free_balance(&bob_account),
+        Currency::reserved_balance(currency, &bob_account)
+    );
+
+    // Locks restrict the same free balance without moving it, and the largest lock applies.
+    runtime.balances.set_lock(*b"staking ", &charlie, 1_000);
+    runtime.balances.extend_lock(*b"vesting ", &charlie, 1_500);
+    runtime.balances.remove_lock(*b"staking ", &charlie);
+    println!(
+        "Reserve and lock events: {:?}",
+        runtime.balances.take_events()
     );
+
+    assert!(runtime.balances.check_total_issuance());
+    println!("Total issuance: {}", runtime.balances.total_issuance());
+
+    // Check the files given on the command line against the claims.
+    verify_files(&runtime, args);
     println!("{:#?}", runtime)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_extrinsic(runtime: &Runtime, nonce: types::Nonce) -> types::Extrinsic {
+        support::Extrinsic::new_signed(
+            &Keyring::Alice.pair(),
+            nonce,
+            RuntimeCall::balances(transfer {
+                to: Keyring::Bob.to_account_id(),
+                amount: 30,
+            }),
+            runtime.system.genesis_hash(),
+        )
+    }
+
+    fn transfer_block(runtime: &Runtime) -> types::Block {
+        let nonce = runtime.system.nonce(&Keyring::Alice.to_account_id());
+        runtime
+            .build_block(vec![transfer_extrinsic(runtime, nonce)])
+            .unwrap()
+    }
+
+    fn fee_of(extrinsic: &types::Extrinsic) -> types::Balance {
+        use codec::Encode;
+        let len = extrinsic.encode().len() as u64;
+        balances::Pallet::<Runtime>::compute_fee(len, extrinsic.call.weight()).unwrap()
+    }
+
+    #[test]
+    fn execute_built_blocks() {
+        let mut runtime = Runtime::new();
+        runtime
+            .balances
+            .set_balance(Keyring::Alice.to_account_id(), 1_000);
+
+        let block_1 = transfer_block(&runtime);
+        let block_1_hash = block_1.header.hash();
+        runtime.execute_block(block_1).unwrap();
+        assert_eq!(runtime.system.parent_hash(), block_1_hash);
+
+        let block_2 = transfer_block(&runtime);
+        assert_eq!(block_2.header.parent_hash, block_1_hash);
+        runtime.execute_block(block_2).unwrap();
+        assert_eq!(
+            runtime.balances.get_balance(Keyring::Bob.to_account_id()),
+            60
+        );
+    }
+
+    #[test]
+    fn reject_invalid_headers() {
+        let mut runtime = Runtime::new();
+        runtime
+            .balances
+            .set_balance(Keyring::Alice.to_account_id(), 1_000);
+        let block = transfer_block(&runtime);
+
+        let mut wrong_parent = block.clone();
+        wrong_parent.header.parent_hash = [1; 32];
+        assert_eq!(
+            runtime.execute_block(wrong_parent),
+            Err(support::DispatchError::Other(
+                "parent hash does not match the last executed block"
+            ))
+        );
+
+        let mut wrong_extrinsics = block.clone();
+        wrong_extrinsics.extrinsics.clear();
+        assert_eq!(
+            runtime.execute_block(wrong_extrinsics),
+            Err(support::DispatchError::Other(
+                "extrinsics root does not match the extrinsics of the block"
+            ))
+        );
+
+        let mut wrong_state = block.clone();
+        wrong_state.header.state_root = [1; 32];
+        assert_eq!(
+            runtime.execute_block(wrong_state),
+            Err(support::DispatchError::Other(
+                "state root does not match the state after execution"
+            ))
+        );
+
+        // Rejected blocks leave no changes, so the original block can still be imported.
+        assert_eq!(runtime.system.block_number(), 0);
+        assert_eq!(
+            runtime.balances.get_balance(Keyring::Alice.to_account_id()),
+            1_000
+        );
+        let fee = fee_of(&block.extrinsics[0]);
+        runtime.execute_block(block).unwrap();
+        assert_eq!(
+            runtime.balances.get_balance(Keyring::Alice.to_account_id()),
+            1_000 - 30 - fee
+        );
+    }
+
+    #[test]
+    fn reject_invalid_signatures() {
+        let mut runtime = Runtime::new();
+        runtime
+            .balances
+            .set_balance(Keyring::Alice.to_account_id(), 1_000);
+        let mut extrinsic = transfer_extrinsic(&runtime, 0);
+        extrinsic.signer = Keyring::Bob.public();
+        assert_eq!(
+            runtime.build_block(vec![extrinsic]).unwrap_err(),
+            support::DispatchError::Other("invalid signature")
+        );
+
+        // An extrinsic signed for another chain is not valid either.
+        let mut other_chain = Runtime::new();
+        other_chain.system.set_genesis_hash([1; 32]);
+        let extrinsic = transfer_extrinsic(&other_chain, 0);
+        let mut block = transfer_block(&runtime);
+        block.extrinsics = vec![extrinsic];
+        block.header.extrinsics_root = support::extrinsics_root(&block.extrinsics);
+        assert_eq!(
+            runtime.execute_block(block),
+            Err(support::DispatchError::Other("invalid signature"))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_nonces() {
+        let mut runtime = Runtime::new();
+        runtime
+            .balances
+            .set_balance(Keyring::Alice.to_account_id(), 1_000);
+
+        let block = transfer_block(&runtime);
+        let replayed = block.extrinsics[0].clone();
+        runtime.execute_block(block).unwrap();
+        assert_eq!(runtime.system.nonce(&Keyring::Alice.to_account_id()), 1);
+
+        // The same extrinsic can't be included again, nor can one from the future.
+        for extrinsic in [replayed, transfer_extrinsic(&runtime, 2)] {
+            assert_eq!(
+                runtime.build_block(vec![extrinsic]).unwrap_err(),
+                support::DispatchError::Other("nonce does not match the nonce of the caller")
+            );
+        }
+        assert!(runtime
+            .build_block(vec![transfer_extrinsic(&runtime, 1)])
+            .is_ok());
+    }
+
+    #[test]
+    fn charge_transaction_fees() {
+        let mut runtime = Runtime::new();
+        let alice = Keyring::Alice.to_account_id();
+
+        // Alice can't pay the fee, so the extrinsic is rejected.
+        let extrinsic = transfer_extrinsic(&runtime, 0);
+        let fee = fee_of(&extrinsic);
+        runtime.balances.set_balance(alice, fee - 1);
+        assert_eq!(
+            runtime.build_block(vec![extrinsic.clone()]).unwrap_err(),
+            support::DispatchError::Other("caller cannot pay the transaction fee")
+        );
+
+        // Alice can pay the fee but not the transfer: the fee is still charged.
+        runtime.balances.set_balance(alice, fee);
+        let block = runtime.build_block(vec![extrinsic]).unwrap();
+        runtime.execute_block(block).unwrap();
+        assert_eq!(runtime.balances.get_balance(alice), 0);
+        // The error of the pallet reaches the caller as a `DispatchError::Module`, identified by
+        // the `#[pallet_index(...)]` of balances.
+        assert!(matches!(
+            runtime.system.events().last().unwrap().event,
+            RuntimeEvent::system(system::Event::ExtrinsicFailed {
+                error: support::DispatchError::Module(support::ModuleError { index: 1, error, .. })
+            }) if error == support::PalletError::index(&balances::Error::InsufficientBalance)
+        ));
+        // The fee is burned.
+        assert_eq!(runtime.balances.total_issuance(), 0);
+        assert!(runtime.balances.check_total_issuance());
+    }
+
+    #[test]
+    fn claims_reserve_a_deposit() {
+        let mut runtime = Runtime::new();
+        let alice = Keyring::Alice.to_account_id();
+        runtime.balances.set_balance(alice, 1_000);
+        let genesis_hash = runtime.system.genesis_hash();
+        let claim = |nonce, call| {
+            support::Extrinsic::new_signed(
+                &Keyring::Alice.pair(),
+                nonce,
+                RuntimeCall::proof_of_existence(call),
+                genesis_hash,
+            )
+        };
+
+        let create = claim(
+            0,
+            create_claim {
+                claim: support::hash(b"document"),
+                lifetime: None,
+            },
+        );
+        let fee = fee_of(&create);
+        let block = runtime.build_block(vec![create]).unwrap();
+        runtime.execute_block(block).unwrap();
+        assert_eq!(runtime.balances.get_balance(alice), 1_000 - fee - 100);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 100);
+
+        let revoke = claim(
+            1,
+            revoke_claim {
+                claim: support::hash(b"document"),
+            },
+        );
+        let revoke_fee = fee_of(&revoke);
+        let block = runtime.build_block(vec![revoke]).unwrap();
+        runtime.execute_block(block).unwrap();
+        assert_eq!(
+            runtime.balances.get_balance(alice),
+            1_000 - fee - revoke_fee
+        );
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert!(runtime.balances.check_total_issuance());
+    }
+
+    #[test]
+    fn claims_expire_at_the_end_of_a_block() {
+        let mut runtime = Runtime::new();
+        let alice = Keyring::Alice.to_account_id();
+        runtime.balances.set_balance(alice, 1_000);
+        let genesis_hash = runtime.system.genesis_hash();
+
+        // Created in block 1, the claim expires at the end of block 3.
+        let create = support::Extrinsic::new_signed(
+            &Keyring::Alice.pair(),
+            0,
+            RuntimeCall::proof_of_existence(create_claim {
+                claim: support::hash(b"document"),
+                lifetime: Some(2),
+            }),
+            genesis_hash,
+        );
+        let block = runtime.build_block(vec![create]).unwrap();
+        runtime.execute_block(block).unwrap();
+        let free = runtime.balances.get_balance(alice);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 100);
+
+        for _ in 0..2 {
+            let block = runtime.build_block(vec![]).unwrap();
+            runtime.execute_block(block).unwrap();
+        }
+        assert!(runtime
+            .proof_of_existence
+            .get_claim(&support::hash(b"document"))
+            .is_none());
+        assert_eq!(runtime.balances.get_balance(alice), free + 100);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert!(matches!(
+            runtime.system.events().last(),
+            Some(system::EventRecord {
+                phase: system::Phase::Finalization,
+                event: RuntimeEvent::proof_of_existence(
+                    proof_of_existence::Event::ClaimExpired { .. }
+                ),
+            })
+        ));
+    }
+
+    #[test]
+    fn build_genesis_state() {
+        let alice = Keyring::Alice.to_account_id();
+        let config = RuntimeGenesisConfig {
+            system: Default::default(),
+            balances: balances::GenesisConfig {
+                balances: vec![(alice, 1_000)],
+                ..Default::default()
+            },
+            proof_of_existence: proof_of_existence::GenesisConfig {
+                claims: vec![(alice, support::hash(b"document"))],
+                ..Default::default()
+            },
+        };
+        let runtime = Runtime::from_genesis(config.clone()).unwrap();
+
+        assert_eq!(runtime.system.block_number(), 0);
+        assert_eq!(runtime.balances.get_balance(alice), 900);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 100);
+        assert_eq!(runtime.balances.total_issuance(), 1_000);
+        assert_eq!(
+            runtime
+                .proof_of_existence
+                .get_claim(&support::hash(b"document"))
+                .map(|claim| claim.owner),
+            Some(alice)
+        );
+        assert!(runtime.system.events().is_empty());
+
+        // The same config always builds the same chain, which differs from the default one.
+        let same = Runtime::from_genesis(config.clone()).unwrap();
+        assert_eq!(same.system.genesis_hash(), runtime.system.genesis_hash());
+        assert_ne!(
+            Runtime::new().system.genesis_hash(),
+            runtime.system.genesis_hash()
+        );
+
+        // The pallets are built in order, so a claim needs an owner endowed by the balances.
+        let unfunded = RuntimeGenesisConfig {
+            balances: Default::default(),
+            ..config
+        };
+        assert_eq!(
+            Runtime::from_genesis(unfunded).unwrap_err(),
+            support::DispatchError::Other("cannot afford the claim deposit")
+        );
+    }
+
+    #[test]
+    fn decode_encoded_blocks() {
+        let mut runtime = Runtime::new();
+        runtime
+            .balances
+            .set_balance(Keyring::Alice.to_account_id(), 10_000);
+        let genesis_hash = runtime.system.genesis_hash();
+        let claim = support::Extrinsic::new_signed(
+            &Keyring::Alice.pair(),
+            1,
+            RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claims_batch {
+                claims: vec![support::hash(b"a"), support::hash(b"b")],
+                lifetime: Some(10),
+            }),
+            genesis_hash,
+        );
+        let block = runtime
+            .build_block(vec![transfer_extrinsic(&runtime, 0), claim])
+            .unwrap();
+
+        let bytes = block.encode();
+        let decoded = types::Block::decode(&bytes).unwrap();
+        assert_eq!(decoded.header, block.header);
+        assert_eq!(decoded.encode(), bytes);
+        assert!(runtime.execute_block(decoded).is_ok());
+
+        assert_eq!(
+            types::Block::decode(&bytes[..bytes.len() - 1]).unwrap_err(),
+            codec::Error::UnexpectedEnd
+        );
+        let mut call = RuntimeCall::balances(transfer {
+            to: Keyring::Bob.to_account_id(),
+            amount: 1,
+        })
+        .encode();
+        call[0] = 9;
+        assert_eq!(
+            RuntimeCall::decode(&call).unwrap_err(),
+            codec::Error::Invalid("unknown pallet")
+        );
+        call[0] = 1;
+        call[1] = 9;
+        assert_eq!(
+            RuntimeCall::decode(&call).unwrap_err(),
+            codec::Error::Invalid("unknown call")
+        );
+    }
+
+    #[test]
+    fn encode_calls_with_their_declared_indices() {
+        // Calls start with the `#[pallet_index(...)]` of their pallet and the `#[call_index(...)]`
+        // of their function, which keep encodings stable when declarations are reordered.
+        let call = RuntimeCall::balances(balances::Call::transfer_keep_alive {
+            to: Keyring::Bob.to_account_id(),
+            amount: 1,
+        });
+        assert_eq!(call.encode()[..2], [1, 1]);
+
+        let call = RuntimeCall::proof_of_existence(create_merkle_claim {
+            root: support::hash(b"root"),
+            lifetime: None,
+        });
+        let bytes = call.encode();
+        assert_eq!(bytes[..2], [2, 2]);
+        assert_eq!(RuntimeCall::decode(&bytes).unwrap().encode(), bytes);
+    }
+
+    #[test]
+    fn reject_overweight_blocks() {
+        let mut runtime = Runtime::new();
+        runtime
+            .balances
+            .set_balance(Keyring::Alice.to_account_id(), 10_000);
+
+        // Every transfer weighs 10, so 10 of them fill a block.
+        let transfers = (0..11)
+            .map(|nonce| transfer_extrinsic(&runtime, nonce))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            runtime.build_block(transfers[..11].to_vec()).unwrap_err(),
+            support::DispatchError::Other("block weight exceeds the maximum block weight")
+        );
+        assert!(runtime.build_block(transfers[..10].to_vec()).is_ok());
+    }
+
+    /// A runtime of two instances of a pallet which only emits an event from each of its hooks, to
+    /// check where the hooks of the pallets run in a block.
+    mod hooks {
+        use crate::support::{
+            self, BuildGenesisConfig, Dispatch, DispatchResult, Hooks, PalletError,
+        };
+        use crate::{codec::Encode, crypto::Keyring, system};
+
+        mod types {
+            pub type AccountId = crate::crypto::AccountId32;
+            pub type BlockNumber = u32;
+            pub type Nonce = u32;
+
+            pub type Extrinsic = crate::support::Extrinsic<Nonce, super::RuntimeCall>;
+            pub type Header = crate::support::Header<BlockNumber>;
+            pub type Block = crate::support::Block<Header, Extrinsic>;
+        }
+
+        mod logger {
+            use super::*;
+
+            pub trait Config: system::Config {}
+
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub enum Error {}
+
+            impl PalletError for Error {
+                fn index(&self) -> u8 {
+                    match *self {}
+                }
+
+                fn as_str(&self) -> &'static str {
+                    match *self {}
+                }
+            }
+
+            #[derive(Debug, Clone, PartialEq)]
+            pub enum Event<T: Config> {
+                Initialized(T::BlockNumber),
+                Finalized(T::BlockNumber),
+            }
+
+            #[derive(Debug, Clone)]
+            pub struct Pallet<T: Config> {
+                events: Vec<Event<T>>,
+            }
+
+            impl<T: Config> Pallet<T> {
+                pub fn new() -> Self {
+                    Self { events: Vec::new() }
+                }
+
+                pub fn take_events(&mut self) -> Vec<Event<T>> {
+                    std::mem::take(&mut self.events)
+                }
+            }
+
+            #[macros::call]
+            impl<T: Config> Pallet<T> {
+                #[call_index(0)]
+                #[weight(0)]
+                pub fn remark(
+                    &mut self,
+                    _caller: T::AccountId,
+                    _who: T::AccountId,
+                ) -> DispatchResult<Error> {
+                    Ok(())
+                }
+            }
+
+            impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {
+                fn on_initialize(&mut self, _runtime: &mut T, block_number: T::BlockNumber) {
+                    self.events.push(Event::Initialized(block_number));
+                }
+
+                fn on_finalize(&mut self, _runtime: &mut T, block_number: T::BlockNumber) {
+                    self.events.push(Event::Finalized(block_number));
+                }
+            }
+
+            impl<T: Config> Encode for Pallet<T> {
+                fn encode_to(&self, _dest: &mut Vec<u8>) {}
+            }
+
+            #[derive(Debug, Clone)]
+            pub struct GenesisConfig<T: Config>(std::marker::PhantomData<T>);
+
+            impl<T: Config> Default for GenesisConfig<T> {
+                fn default() -> Self {
+                    Self(std::marker::PhantomData)
+                }
+            }
+
+            impl<T: Config> BuildGenesisConfig<Pallet<T>, T> for GenesisConfig<T> {
+                fn build(
+                    &self,
+                    _pallet: &mut Pallet<T>,
+                    _runtime: &mut T,
+                ) -> support::DispatchResult {
+                    Ok(())
+                }
+            }
+        }
+
+        // The runtime expects a module for each pallet, named after it.
+        mod first {
+            pub use super::logger::*;
+        }
+
+        mod second {
+            pub use super::logger::*;
+        }
+
+        #[derive(Debug, Clone)]
+        #[macros::runtime]
+        pub struct Runtime {
+            system: system::Pallet<Self>,
+            #[pallet_index(1)]
+            first: logger::Pallet<Self>,
+            #[pallet_index(2)]
+            second: logger::Pallet<Self>,
+        }
+
+        impl system::Config for Runtime {
+            type AccountId = types::AccountId;
+            type BlockNumber = types::BlockNumber;
+            type Nonce = types::Nonce;
+            type RuntimeEvent = RuntimeEvent;
+
+            const MAX_BLOCK_WEIGHT: support::Weight = 100;
+        }
+
+        impl logger::Config for Runtime {}
+
+        #[test]
+        fn hooks_run_in_pallet_order_around_the_extrinsics() {
+            let mut runtime = Runtime::new();
+            let remark = support::Extrinsic::new_signed(
+                &Keyring::Alice.pair(),
+                0,
+                RuntimeCall::second(logger::Call::remark {
+                    _who: Keyring::Bob.to_account_id(),
+                }),
+                runtime.system.genesis_hash(),
+            );
+            let block = runtime.build_block(vec![remark]).unwrap();
+            runtime.execute_block(block).unwrap();
+
+            let events = runtime
+                .system
+                .events()
+                .iter()
+                .map(|record| (record.phase, record.event.clone()))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events[..],
+                [
+                    (
+                        system::Phase::Initialization,
+                        RuntimeEvent::first(logger::Event::Initialized(1))
+                    ),
+                    (
+                        system::Phase::Initialization,
+                        RuntimeEvent::second(logger::Event::Initialized(1))
+                    ),
+                    (
+                        system::Phase::ApplyExtrinsic(0),
+                        RuntimeEvent::system(system::Event::ExtrinsicSuccess)
+                    ),
+                    (
+                        system::Phase::Finalization,
+                        RuntimeEvent::first(logger::Event::Finalized(1))
+                    ),
+                    (
+                        system::Phase::Finalization,
+                        RuntimeEvent::second(logger::Event::Finalized(1))
+                    ),
+                ]
+            ));
+        }
+    }
+}
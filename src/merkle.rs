@@ -0,0 +1,152 @@
+//! Merkle trees let a single hash commit to a whole list of documents, while any one of them can
+//! later be proven to be part of the list with a proof of logarithmic size.
+//!
+//! The functions are generic over the hash type `H` and the hash function, so a pallet can build
+//! trees of its own `Content`. Leaves and inner nodes are hashed with a different prefix, so an
+//! inner node can never be passed off as a leaf. When a level has an odd number of nodes, the last
+//! one is promoted to the next level as is.
+
+use crate::codec::Encode;
+
+/// The prefix of the data hashed for a leaf.
+const LEAF_PREFIX: u8 = 0;
+/// The prefix of the data hashed for an inner node.
+const NODE_PREFIX: u8 = 1;
+
+/// One step of a `MerkleProof`: the sibling of the current node, on its left or on its right.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofStep<H> {
+    Left(H),
+    Right(H),
+}
+
+/// A proof that a leaf is part of the Merkle tree with a given root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerkleProof<H> {
+    /// The siblings of the nodes on the path from the leaf to the root, from the bottom up. Nodes
+    /// promoted without a sibling have no step.
+    pub steps: Vec<ProofStep<H>>,
+}
+
+impl<H: Encode> MerkleProof<H> {
+    /// The root of the Merkle tree which this proof shows `leaf` to be part of. The proof holds if
+    /// this is the root which is expected.
+    pub fn root(&self, leaf: &H, hash: impl Fn(&[u8]) -> H) -> H {
+        self.steps
+            .iter()
+            .fold(hash_leaf(leaf, &hash), |node, step| match step {
+                ProofStep::Left(sibling) => hash_node(sibling, &node, &hash),
+                ProofStep::Right(sibling) => hash_node(&node, sibling, &hash),
+            })
+    }
+}
+
+fn hash_leaf<H: Encode>(leaf: &H, hash: impl Fn(&[u8]) -> H) -> H {
+    hash(&(LEAF_PREFIX, leaf).encode())
+}
+
+fn hash_node<H: Encode>(left: &H, right: &H, hash: impl Fn(&[u8]) -> H) -> H {
+    hash(&(NODE_PREFIX, (left, right)).encode())
+}
+
+/// Hash the level above `nodes`.
+fn parent_level<H: Encode + Clone>(nodes: &[H], hash: impl Fn(&[u8]) -> H) -> Vec<H> {
+    nodes
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_node(left, right, &hash),
+            [last] => last.clone(),
+            _ => unreachable!("chunks have one or two nodes"),
+        })
+        .collect()
+}
+
+/// The root of the Merkle tree of `leaves`, or `None` if there are no leaves.
+pub fn merkle_root<H: Encode + Clone>(leaves: &[H], hash: impl Fn(&[u8]) -> H) -> Option<H> {
+    let mut level = leaves
+        .iter()
+        .map(|leaf| hash_leaf(leaf, &hash))
+        .collect::<Vec<_>>();
+    while level.len() > 1 {
+        level = parent_level(&level, &hash);
+    }
+    level.pop()
+}
+
+/// A proof that the leaf at `index` is part of the Merkle tree of `leaves`, or `None` if there is
+/// no such leaf.
+pub fn merkle_proof<H: Encode + Clone>(
+    leaves: &[H],
+    mut index: usize,
+    hash: impl Fn(&[u8]) -> H,
+) -> Option<MerkleProof<H>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut level = leaves
+        .iter()
+        .map(|leaf| hash_leaf(leaf, &hash))
+        .collect::<Vec<_>>();
+    let mut steps = Vec::new();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            let node = level[sibling].clone();
+            steps.push(if sibling < index {
+                ProofStep::Left(node)
+            } else {
+                ProofStep::Right(node)
+            });
+        }
+        level = parent_level(&level, &hash);
+        index /= 2;
+    }
+    Some(MerkleProof { steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merkle_proof, merkle_root, ProofStep};
+    use crate::support::{hash, Hash};
+
+    fn leaves(count: usize) -> Vec<Hash> {
+        (0..count).map(|i| hash(&[i as u8])).collect()
+    }
+
+    #[test]
+    fn every_leaf_can_be_proven() {
+        assert_eq!(merkle_root::<Hash>(&[], hash), None);
+        assert!(merkle_proof::<Hash>(&[], 0, hash).is_none());
+
+        for count in 1..=9 {
+            let leaves = leaves(count);
+            let root = merkle_root(&leaves, hash).unwrap();
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof(&leaves, index, hash).unwrap();
+                assert_eq!(proof.root(leaf, hash), root);
+            }
+            assert!(merkle_proof(&leaves, count, hash).is_none());
+        }
+    }
+
+    #[test]
+    fn proofs_only_hold_for_their_leaf() {
+        let leaves = leaves(5);
+        let root = merkle_root(&leaves, hash).unwrap();
+        let proof = merkle_proof(&leaves, 2, hash).unwrap();
+
+        assert_ne!(proof.root(&leaves[3], hash), root);
+        assert_ne!(proof.root(&hash(b"not a leaf"), hash), root);
+
+        // Swapping a sibling to the other side breaks the proof.
+        let mut swapped = proof.clone();
+        swapped.steps[0] = match &proof.steps[0] {
+            ProofStep::Left(node) => ProofStep::Right(*node),
+            ProofStep::Right(node) => ProofStep::Left(*node),
+        };
+        assert_ne!(swapped.root(&leaves[2], hash), root);
+
+        // Even a single leaf is hashed, so the root is never one of the leaves.
+        assert_ne!(merkle_root(&leaves[..1], hash), Some(leaves[0]));
+    }
+}
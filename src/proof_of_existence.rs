@@ -1,47 +1,357 @@
-use crate::support::DispatchResult;
+use crate::codec::Encode;
+use crate::merkle::{self, MerkleProof};
+use crate::support::{
+    BuildGenesisConfig, Currency, DispatchError, DispatchResult, Hooks, PalletError,
+};
 use core::fmt::Debug;
-use std::collections::BTreeMap;
+use num::traits::{CheckedAdd, Zero};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::path::Path;
 
 pub trait Config: crate::system::Config {
     /// The type which represents the content that can be claimed using this pallet.
     /// Could be the content directly as bytes, or better yet the hash of that content.
     /// We leave that decision to the runtime developer.
-    type Content: Debug + Ord;
+    type Content: Debug + Ord + Clone + Encode;
+
+    /// Turn a document into the `Content` which is claimed, usually by hashing it, so the chain
+    /// never stores the document itself.
+    fn hash_content(data: &[u8]) -> Self::Content;
+
+    /// The number of the block being executed, as known by the System Pallet of the runtime.
+    fn block_number(&self) -> Self::BlockNumber;
+    /// The time at which the block being executed was produced, as milliseconds since the Unix
+    /// epoch, if the runtime knows it.
+    fn timestamp(&self) -> Option<u64>;
+
+    /// The currency used for the claim deposits.
+    type Currency: Currency<Self::AccountId>;
+    /// The default amount reserved from the owner of a claim for as long as the claim exists, so
+    /// that claims are not free to spam. The genesis config of a chain can override it.
+    const CLAIM_DEPOSIT: BalanceOf<Self>;
+    /// The default maximum number of claims a single account can own, which the genesis config
+    /// of a chain can override.
+    const MAX_CLAIMS_PER_ACCOUNT: u32;
+
+    /// The currency of the runtime, usually the Balances Pallet.
+    fn currency(&mut self) -> &mut Self::Currency;
+}
+
+/// The balance type of the currency used by the pallet for a given `Config`.
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as crate::system::Config>::AccountId>>::Balance;
+
+/// A claim on some content, recording who owns it, when it was made, when it expires and the
+/// deposit reserved for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Claim<AccountId, BlockNumber, Balance> {
+    pub owner: AccountId,
+    /// The block in which the claim was created.
+    pub block_number: BlockNumber,
+    /// The time at which the claim was created, see `Config::timestamp`.
+    pub timestamp: Option<u64>,
+    /// The deposit reserved from the owner, which is returned when the claim is revoked.
+    pub deposit: Balance,
+    /// The block from which the claim is expired, or `None` if the claim never expires. An
+    /// expired claim can be revoked by anyone, and is purged at the end of that block.
+    pub expires_at: Option<BlockNumber>,
+    /// Whether the claimed content is the Merkle root of a list of documents, see
+    /// `Pallet::create_merkle_claim`.
+    pub merkle: bool,
+}
+
+impl<AccountId, BlockNumber: Ord, Balance> Claim<AccountId, BlockNumber, Balance> {
+    /// Whether the claim is expired in block `block_number`.
+    pub fn is_expired(&self, block_number: &BlockNumber) -> bool {
+        self.expires_at
+            .as_ref()
+            .is_some_and(|expires_at| expires_at <= block_number)
+    }
+}
+
+/// The claims stored by the pallet for a given `Config`.
+pub type ClaimOf<T> = Claim<
+    <T as crate::system::Config>::AccountId,
+    <T as crate::system::Config>::BlockNumber,
+    BalanceOf<T>,
+>;
+
+impl<AccountId: Encode, BlockNumber: Encode, Balance: Encode> Encode
+    for Claim<AccountId, BlockNumber, Balance>
+{
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.owner.encode_to(dest);
+        self.block_number.encode_to(dest);
+        self.timestamp.encode_to(dest);
+        self.deposit.encode_to(dest);
+        self.expires_at.encode_to(dest);
+        self.merkle.encode_to(dest);
+    }
+}
+
+/// The events emitted by the Proof of Existence Module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+    /// `owner` claimed `claim`.
+    ClaimCreated {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    /// The claim of `owner` on `claim` was revoked.
+    ClaimRevoked {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    /// `owner` changed when their claim on `claim` expires.
+    ClaimRenewed {
+        owner: T::AccountId,
+        claim: T::Content,
+        expires_at: Option<T::BlockNumber>,
+    },
+    /// The claim of `owner` on `claim` expired and was purged.
+    ClaimExpired {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    /// The owner of `claim` changed from `from` to `to`.
+    OwnerChanged {
+        from: T::AccountId,
+        to: T::AccountId,
+        claim: T::Content,
+    },
+}
+
+/// The errors which can be returned by the calls of the Proof of Existence Module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The content has already been claimed.
+    AlreadyClaimed,
+    /// There is no claim on the content.
+    ClaimNotFound,
+    /// The claim is owned by another account.
+    NotClaimOwner,
+    /// The claim is already owned by the account it would be transferred to.
+    TransferToSelf,
+    /// The account can't afford the deposit of a claim.
+    InsufficientBalance,
+    /// The claim is expired, so it can only be revoked.
+    ClaimExpired,
+    /// The block at which the claim would expire can't be represented.
+    LifetimeOverflow,
+    /// The account already owns the maximum number of claims.
+    TooManyClaims,
+    /// A lifetime of zero blocks would make the claim expired as soon as it is created.
+    InvalidLifetime,
+}
+
+impl PalletError for Error {
+    fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Error::AlreadyClaimed => "this content is already claimed",
+            Error::ClaimNotFound => "claim not found",
+            Error::NotClaimOwner => "this claim is owned by someone else",
+            Error::TransferToSelf => "cannot transfer a claim to its owner",
+            Error::InsufficientBalance => "cannot afford the claim deposit",
+            Error::ClaimExpired => "this claim is expired",
+            Error::LifetimeOverflow => "the claim lifetime is too long",
+            Error::TooManyClaims => "this account owns too many claims",
+            Error::InvalidLifetime => "the claim lifetime must be at least one block",
+        }
+    }
 }
 
 /// This is the Proof of Existence Module.
 /// It is a simple module that allows accounts to claim existence of some data.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
-    /// A simple storage map from content to the owner of that content.
+    /// A simple storage map from content to the claim on that content.
     /// Accounts can make multiple different claims, but each claim can only have one owner.
-    claims: BTreeMap<T::Content, T::AccountId>,
+    claims: BTreeMap<T::Content, ClaimOf<T>>,
+    /// The content of the claims owned by each account, kept in sync with `claims`.
+    owned: BTreeMap<T::AccountId, BTreeSet<T::Content>>,
+    /// The claims which expire in each block, so expired claims can be purged without going
+    /// through all the claims.
+    expiries: BTreeMap<T::BlockNumber, Vec<T::Content>>,
+    /// The amount reserved from the owner of a new claim.
+    claim_deposit: BalanceOf<T>,
+    /// The maximum number of claims a single account can own.
+    max_claims_per_account: u32,
+    /// Events emitted by this pallet which have not yet been collected by the runtime.
+    events: Vec<Event<T>>,
 }
 
 #[macros::call]
 impl<T: Config> Pallet<T> {
-    /// Create a new claim on behalf of the `caller`.
-    /// This function will return an error if someone already has claimed that content.
-    pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        if self.claims.contains_key(&claim) {
-            return Err("this content is already claimed");
-        }
+    /// Create a new claim on behalf of the `caller`, recording the current block number and time.
+    /// A claim with a `lifetime` expires that many blocks after the current block, otherwise it
+    /// never expires. A lifetime of zero blocks is rejected, since the claim would already be
+    /// expired.
+    /// This function will return an error if someone already has claimed that content, if the
+    /// `caller` owns too many claims, or if the `caller` can't afford the claim deposit.
+    #[call_index(0)]
+    #[weight(20)]
+    pub fn create_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+        lifetime: Option<T::BlockNumber>,
+    ) -> DispatchResult<Error> {
+        self.do_create_claim(runtime, caller, claim, lifetime, false)
+    }
 
-        self.claims.insert(claim, caller);
+    /// Create a claim on each of `claims` on behalf of the `caller`, all with the same `lifetime`,
+    /// as if `create_claim` was called for each of them.
+    /// If any of the claims can't be created the call fails, and the runtime rolls back the claims
+    /// created before it.
+    #[call_index(1)]
+    #[weight(20 * claims.len() as u64)]
+    pub fn create_claims_batch(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claims: Vec<T::Content>,
+        lifetime: Option<T::BlockNumber>,
+    ) -> DispatchResult<Error> {
+        for claim in claims {
+            self.do_create_claim(runtime, caller.clone(), claim, lifetime, false)?;
+        }
         Ok(())
     }
 
-    /// Revoke an existing claim on some content.
-    /// This function should only succeed if the caller is the owner of an existing claim.
-    /// It will return an error if the claim does not exist, or if the caller is not the owner.
-    pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        let owner = self.claims.get(&claim).ok_or("claim not found")?;
+    /// Create a claim on the Merkle root of a list of documents on behalf of the `caller`, which
+    /// claims all of the documents for the cost of a single claim. See `Pallet::merkle_root` to
+    /// compute the root, and `Pallet::verify_merkle_claim` to later prove a single document.
+    /// This function fails in the same cases as `create_claim`.
+    #[call_index(2)]
+    #[weight(20)]
+    pub fn create_merkle_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        root: T::Content,
+        lifetime: Option<T::BlockNumber>,
+    ) -> DispatchResult<Error> {
+        self.do_create_claim(runtime, caller, root, lifetime, true)
+    }
+
+    /// Revoke an existing claim on some content, and return what is left of its deposit to the
+    /// owner.
+    /// This function should only succeed if the caller is the owner of an existing claim, or if
+    /// the claim is expired, in which case anyone can revoke it.
+    /// It will return an error if the claim does not exist, or if the caller can't revoke it.
+    #[call_index(3)]
+    #[weight(10)]
+    pub fn revoke_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+    ) -> DispatchResult<Error> {
+        let record = self.claims.get(&claim).ok_or(Error::ClaimNotFound)?;
 
-        if owner != &caller {
-            return Err("this claim is owned by someone else");
+        if record.owner != caller && !record.is_expired(&runtime.block_number()) {
+            return Err(Error::NotClaimOwner);
         }
 
+        let expires_at = record.expires_at;
+        let owner = record.owner.clone();
+        Self::release_deposit(runtime, &owner, record.deposit);
         self.claims.remove(&claim);
+        self.unschedule_expiry(expires_at, &claim);
+        self.remove_owned(&owner, &claim);
+        self.deposit_event(Event::ClaimRevoked { owner, claim });
+        Ok(())
+    }
+
+    /// Change when an existing claim expires, to `lifetime` blocks after the current block, which
+    /// must be at least one block, or never if `lifetime` is `None`.
+    /// Only the owner of the claim can renew it, and only before it expires.
+    #[call_index(4)]
+    #[weight(10)]
+    pub fn renew_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+        lifetime: Option<T::BlockNumber>,
+    ) -> DispatchResult<Error> {
+        let block_number = runtime.block_number();
+        let record = self.claims.get_mut(&claim).ok_or(Error::ClaimNotFound)?;
+
+        if record.owner != caller {
+            return Err(Error::NotClaimOwner);
+        }
+        if record.is_expired(&block_number) {
+            return Err(Error::ClaimExpired);
+        }
+
+        let expires_at = Self::expires_at(block_number, lifetime)?;
+        let previous = core::mem::replace(&mut record.expires_at, expires_at);
+        self.unschedule_expiry(previous, &claim);
+        self.schedule_expiry(expires_at, &claim);
+        self.deposit_event(Event::ClaimRenewed {
+            owner: caller,
+            claim,
+            expires_at,
+        });
+        Ok(())
+    }
+
+    /// Hand an existing claim over to `to`.
+    /// Only the owner of the claim can transfer it, not to themselves, not to an account which
+    /// owns too many claims, and only before it expires.
+    /// The claim keeps the block number and time at which it was created and when it expires, but
+    /// its deposit is now reserved from `to`, and the deposit of the `caller` is returned.
+    #[call_index(5)]
+    #[weight(10)]
+    pub fn transfer_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+        to: T::AccountId,
+    ) -> DispatchResult<Error> {
+        let block_number = runtime.block_number();
+        let record = self.claims.get_mut(&claim).ok_or(Error::ClaimNotFound)?;
+
+        if record.owner != caller {
+            return Err(Error::NotClaimOwner);
+        }
+        if record.is_expired(&block_number) {
+            return Err(Error::ClaimExpired);
+        }
+        if to == caller {
+            return Err(Error::TransferToSelf);
+        }
+        if self
+            .owned
+            .get(&to)
+            .is_some_and(|claims| claims.len() >= self.max_claims_per_account as usize)
+        {
+            return Err(Error::TooManyClaims);
+        }
+
+        // The new deposit is reserved first, so nothing changes if `to` can't afford it. The old
+        // deposit may have been partly slashed, so the caller gets back what is left of it.
+        runtime
+            .currency()
+            .reserve(&to, record.deposit)
+            .map_err(|_| Error::InsufficientBalance)?;
+        Self::release_deposit(runtime, &caller, record.deposit);
+        record.owner = to.clone();
+        self.remove_owned(&caller, &claim);
+        self.add_owned(&to, &claim);
+        self.deposit_event(Event::OwnerChanged {
+            from: caller,
+            to,
+            claim,
+        });
         Ok(())
     }
 }
@@ -51,74 +361,933 @@ impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
         Pallet {
             claims: BTreeMap::new(),
+            owned: BTreeMap::new(),
+            expiries: BTreeMap::new(),
+            claim_deposit: T::CLAIM_DEPOSIT,
+            max_claims_per_account: T::MAX_CLAIMS_PER_ACCOUNT,
+            events: Vec::new(),
         }
     }
 
-    /// Get the owner (if any) of a claim.
-    pub fn get_claim(&self, claim: &T::Content) -> Option<&T::AccountId> {
+    /// Get the claim (if any) on some content, with its owner and creation time.
+    pub fn get_claim(&self, claim: &T::Content) -> Option<&ClaimOf<T>> {
         self.claims.get(claim)
     }
+
+    /// The claims owned by `who`, ordered by their content.
+    ///
+    /// At most `limit` claims are returned, starting after the content `start_after` if given, so
+    /// the claims of an account can be read one page at a time by passing the content of the last
+    /// claim of a page as `start_after` for the next one.
+    pub fn claims_of(
+        &self,
+        who: &T::AccountId,
+        start_after: Option<&T::Content>,
+        limit: usize,
+    ) -> Vec<(&T::Content, &ClaimOf<T>)> {
+        let Some(owned) = self.owned.get(who) else {
+            return Vec::new();
+        };
+        let start = start_after.map_or(Bound::Unbounded, Bound::Excluded);
+        owned
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .filter_map(|content| Some((content, self.claims.get(content)?)))
+            .collect()
+    }
+
+    /// The number of claims owned by `who`.
+    pub fn claim_count(&self, who: &T::AccountId) -> usize {
+        self.owned.get(who).map_or(0, BTreeSet::len)
+    }
+
+    /// The amount reserved from the owner of a new claim.
+    pub fn claim_deposit(&self) -> BalanceOf<T> {
+        self.claim_deposit
+    }
+
+    /// The maximum number of claims a single account can own.
+    pub fn max_claims_per_account(&self) -> u32 {
+        self.max_claims_per_account
+    }
+
+    /// The Merkle root of a list of documents, given the `Content` of each document, which can be
+    /// claimed with `create_merkle_claim`. Returns `None` if there are no documents.
+    pub fn merkle_root(documents: &[T::Content]) -> Option<T::Content> {
+        merkle::merkle_root(documents, T::hash_content)
+    }
+
+    /// A proof that the document at `index` is part of the list of `documents`, which can be
+    /// checked with `verify_merkle_claim` once their Merkle root is claimed.
+    pub fn merkle_proof(documents: &[T::Content], index: usize) -> Option<MerkleProof<T::Content>> {
+        merkle::merkle_proof(documents, index, T::hash_content)
+    }
+
+    /// Get the Merkle claim (if any) which `proof` shows to include `document`.
+    pub fn verify_merkle_claim(
+        &self,
+        document: &T::Content,
+        proof: &MerkleProof<T::Content>,
+    ) -> Option<&ClaimOf<T>> {
+        self.get_claim(&proof.root(document, T::hash_content))
+            .filter(|claim| claim.merkle)
+    }
+
+    /// The block from which a claim created or renewed in `block_number` with `lifetime` expires.
+    /// The lifetime must be at least one block.
+    fn expires_at(
+        block_number: T::BlockNumber,
+        lifetime: Option<T::BlockNumber>,
+    ) -> Result<Option<T::BlockNumber>, Error> {
+        lifetime
+            .map(|lifetime| {
+                if lifetime.is_zero() {
+                    return Err(Error::InvalidLifetime);
+                }
+                block_number
+                    .checked_add(&lifetime)
+                    .ok_or(Error::LifetimeOverflow)
+            })
+            .transpose()
+    }
+
+    /// Create a new claim, see `create_claim`, on the Merkle root of some documents if `merkle`.
+    fn do_create_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+        lifetime: Option<T::BlockNumber>,
+        merkle: bool,
+    ) -> DispatchResult<Error> {
+        if self.claims.contains_key(&claim) {
+            return Err(Error::AlreadyClaimed);
+        }
+
+        self.ensure_can_own(&caller)?;
+        let block_number = runtime.block_number();
+        let expires_at = Self::expires_at(block_number, lifetime)?;
+        runtime
+            .currency()
+            .reserve(&caller, self.claim_deposit)
+            .map_err(|_| Error::InsufficientBalance)?;
+        self.claims.insert(
+            claim.clone(),
+            Claim {
+                owner: caller.clone(),
+                block_number,
+                timestamp: runtime.timestamp(),
+                deposit: self.claim_deposit,
+                expires_at,
+                merkle,
+            },
+        );
+        self.schedule_expiry(expires_at, &claim);
+        self.add_owned(&caller, &claim);
+        self.deposit_event(Event::ClaimCreated {
+            owner: caller,
+            claim,
+        });
+        Ok(())
+    }
+
+    /// Check that `who` can own one more claim.
+    fn ensure_can_own(&self, who: &T::AccountId) -> DispatchResult<Error> {
+        if self.claim_count(who) >= self.max_claims_per_account as usize {
+            return Err(Error::TooManyClaims);
+        }
+        Ok(())
+    }
+
+    /// Record that `who` owns `claim`.
+    fn add_owned(&mut self, who: &T::AccountId, claim: &T::Content) {
+        self.owned
+            .entry(who.clone())
+            .or_default()
+            .insert(claim.clone());
+    }
+
+    /// Record that `who` no longer owns `claim`.
+    fn remove_owned(&mut self, who: &T::AccountId, claim: &T::Content) {
+        if let Some(claims) = self.owned.get_mut(who) {
+            claims.remove(claim);
+            if claims.is_empty() {
+                self.owned.remove(who);
+            }
+        }
+    }
+
+    /// Record that `claim` expires at `expires_at`, so it is purged by `Hooks::on_finalize`.
+    fn schedule_expiry(&mut self, expires_at: Option<T::BlockNumber>, claim: &T::Content) {
+        if let Some(expires_at) = expires_at {
+            self.expiries
+                .entry(expires_at)
+                .or_default()
+                .push(claim.clone());
+        }
+    }
+
+    /// Forget that `claim` expires at `expires_at`.
+    fn unschedule_expiry(&mut self, expires_at: Option<T::BlockNumber>, claim: &T::Content) {
+        let Some(expires_at) = expires_at else {
+            return;
+        };
+        if let Some(claims) = self.expiries.get_mut(&expires_at) {
+            claims.retain(|other| other != claim);
+            if claims.is_empty() {
+                self.expiries.remove(&expires_at);
+            }
+        }
+    }
+
+    /// Return the deposit of a claim to its `owner`. Part or all of the deposit may have been
+    /// slashed while the claim existed, so only what is left of it is unreserved, and the rest is
+    /// lost.
+    fn release_deposit(runtime: &mut T, owner: &T::AccountId, deposit: BalanceOf<T>) {
+        let currency = runtime.currency();
+        let reserved = currency.reserved_balance(owner);
+        let left = if reserved < deposit {
+            reserved
+        } else {
+            deposit
+        };
+        // The free balance of the owner can't overflow, since the deposit came out of it.
+        let _ = currency.unreserve(owner, left);
+    }
+
+    /// Hash the content of the file at `path` into the `Content` which would be claimed for it.
+    pub fn hash_file(path: impl AsRef<Path>) -> std::io::Result<T::Content> {
+        Ok(T::hash_content(&std::fs::read(path)?))
+    }
+
+    /// Get the claim (if any) on the content of the file at `path`.
+    pub fn verify_file(&self, path: impl AsRef<Path>) -> std::io::Result<Option<&ClaimOf<T>>> {
+        Ok(self.get_claim(&Self::hash_file(path)?))
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    /// Drain the events emitted since the last call. The runtime uses this to move them into the
+    /// System Pallet after each extrinsic.
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// The parameters of the pallet for a chain, and the claims which exist from its start.
+#[derive(Debug, Clone)]
+pub struct GenesisConfig<T: Config> {
+    /// The owner and content of each claim. The claims never expire, and their deposit is
+    /// reserved from their owner like for `create_claim`.
+    pub claims: Vec<(T::AccountId, T::Content)>,
+    /// The amount reserved from the owner of a claim, or `None` for `Config::CLAIM_DEPOSIT`.
+    pub claim_deposit: Option<BalanceOf<T>>,
+    /// The maximum number of claims a single account can own, or `None` for
+    /// `Config::MAX_CLAIMS_PER_ACCOUNT`.
+    pub max_claims_per_account: Option<u32>,
+}
+
+impl<T: Config> Default for GenesisConfig<T> {
+    fn default() -> Self {
+        GenesisConfig {
+            claims: Vec::new(),
+            claim_deposit: None,
+            max_claims_per_account: None,
+        }
+    }
+}
+
+/// The parameters are set first, so the genesis claims follow them.
+impl<T: Config> BuildGenesisConfig<Pallet<T>, T> for GenesisConfig<T> {
+    fn build(&self, pallet: &mut Pallet<T>, runtime: &mut T) -> DispatchResult {
+        if let Some(claim_deposit) = self.claim_deposit {
+            pallet.claim_deposit = claim_deposit;
+        }
+        if let Some(max_claims_per_account) = self.max_claims_per_account {
+            pallet.max_claims_per_account = max_claims_per_account;
+        }
+        for (owner, claim) in &self.claims {
+            pallet
+                .do_create_claim(runtime, owner.clone(), claim.clone(), None, false)
+                .map_err(|error| DispatchError::Other(error.as_str()))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {
+    /// Purge the claims which expire in `block_number`, returning their deposits to their owners.
+    fn on_finalize(&mut self, runtime: &mut T, block_number: T::BlockNumber) {
+        while let Some(entry) = self.expiries.first_entry() {
+            if *entry.key() > block_number {
+                break;
+            }
+            for claim in entry.remove() {
+                let Some(record) = self.claims.remove(&claim) else {
+                    continue;
+                };
+                Self::release_deposit(runtime, &record.owner, record.deposit);
+                self.remove_owned(&record.owner, &claim);
+                self.deposit_event(Event::ClaimExpired {
+                    owner: record.owner,
+                    claim,
+                });
+            }
+        }
+    }
+}
+
+impl<T: Config> Encode for Pallet<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.claims.encode_to(dest);
+        self.owned.encode_to(dest);
+        self.expiries.encode_to(dest);
+        self.claim_deposit.encode_to(dest);
+        self.max_claims_per_account.encode_to(dest);
+    }
 }
 
 #[cfg(test)]
 mod test {
-    struct TestConfig;
+    use super::{Claim, Error, Event};
+    use crate::support::{hash, Currency, DispatchResult, Hash, Hooks, PalletError};
+    use std::collections::BTreeMap;
+
+    /// A currency which only keeps track of balances, without any of the rules of the Balances
+    /// Pallet.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct MockCurrency {
+        free: BTreeMap<&'static str, u64>,
+        reserved: BTreeMap<&'static str, u64>,
+    }
+
+    /// The only way the mock currency fails.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct OutOfRange;
+
+    impl PalletError for OutOfRange {
+        fn index(&self) -> u8 {
+            0
+        }
+
+        fn as_str(&self) -> &'static str {
+            "mock balance out of range"
+        }
+    }
+
+    impl MockCurrency {
+        fn change(
+            balances: &mut BTreeMap<&'static str, u64>,
+            who: &'static str,
+            f: impl FnOnce(u64) -> Option<u64>,
+        ) -> DispatchResult<OutOfRange> {
+            let balance = balances.entry(who).or_default();
+            *balance = f(*balance).ok_or(OutOfRange)?;
+            Ok(())
+        }
+    }
+
+    impl Currency<&'static str> for MockCurrency {
+        type Balance = u64;
+        type Error = OutOfRange;
+
+        fn free_balance(&self, who: &&'static str) -> u64 {
+            self.free.get(who).copied().unwrap_or_default()
+        }
+
+        fn reserved_balance(&self, who: &&'static str) -> u64 {
+            self.reserved.get(who).copied().unwrap_or_default()
+        }
+
+        fn transfer(
+            &mut self,
+            from: &&'static str,
+            to: &&'static str,
+            amount: u64,
+        ) -> DispatchResult<OutOfRange> {
+            self.withdraw(from, amount)?;
+            self.deposit(to, amount)
+        }
+
+        fn withdraw(&mut self, who: &&'static str, amount: u64) -> DispatchResult<OutOfRange> {
+            Self::change(&mut self.free, who, |free| free.checked_sub(amount))
+        }
+
+        fn deposit(&mut self, who: &&'static str, amount: u64) -> DispatchResult<OutOfRange> {
+            Self::change(&mut self.free, who, |free| free.checked_add(amount))
+        }
+
+        fn reserve(&mut self, who: &&'static str, amount: u64) -> DispatchResult<OutOfRange> {
+            self.withdraw(who, amount)?;
+            Self::change(&mut self.reserved, who, |reserved| {
+                reserved.checked_add(amount)
+            })
+        }
+
+        fn unreserve(&mut self, who: &&'static str, amount: u64) -> DispatchResult<OutOfRange> {
+            Self::change(&mut self.reserved, who, |reserved| {
+                reserved.checked_sub(amount)
+            })?;
+            self.deposit(who, amount)
+        }
+    }
+
+    /// The pallet reads the block number, time and currency from the runtime, so our test runtime
+    /// is just a place to set them.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct TestConfig {
+        block_number: u32,
+        timestamp: Option<u64>,
+        currency: MockCurrency,
+    }
+
+    /// A test runtime where alice, bob and charlie can each afford a few claims.
+    fn runtime() -> TestConfig {
+        let mut runtime = TestConfig::default();
+        for who in ["alice", "bob", "charlie"] {
+            runtime.currency.free.insert(who, 100);
+        }
+        runtime
+    }
 
     impl super::Config for TestConfig {
-        type Content = &'static str;
+        type Content = Hash;
+
+        fn hash_content(data: &[u8]) -> Hash {
+            hash(data)
+        }
+
+        fn block_number(&self) -> u32 {
+            self.block_number
+        }
+
+        fn timestamp(&self) -> Option<u64> {
+            self.timestamp
+        }
+
+        type Currency = MockCurrency;
+        const CLAIM_DEPOSIT: u64 = 10;
+        const MAX_CLAIMS_PER_ACCOUNT: u32 = 3;
+
+        fn currency(&mut self) -> &mut MockCurrency {
+            &mut self.currency
+        }
     }
 
     impl crate::system::Config for TestConfig {
         type AccountId = &'static str;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = ();
+
+        const MAX_BLOCK_WEIGHT: u64 = 100;
     }
 
     #[test]
     fn create_and_claim_proof_of_existence() {
+        let mut runtime = runtime();
         let mut poe = super::Pallet::<TestConfig>::new();
         let alice = "alice";
-        let content = "Hello";
+        let content = hash(b"Hello");
 
         assert!(poe.get_claim(&content).is_none());
 
-        let _ = poe.create_claim(alice, content);
-        assert_eq!(poe.get_claim(&content), Some(&alice));
+        let _ = poe.create_claim(&mut runtime, alice, content, None);
+        assert_eq!(
+            poe.get_claim(&content).map(|claim| claim.owner),
+            Some(alice)
+        );
+    }
+
+    #[test]
+    fn claims_record_when_they_were_created() {
+        let mut runtime = TestConfig {
+            block_number: 7,
+            timestamp: Some(1_700_000_000_000),
+            ..runtime()
+        };
+        let mut poe = super::Pallet::<TestConfig>::new();
+        let content = hash(b"Hello");
+
+        assert_eq!(
+            poe.create_claim(&mut runtime, "alice", content, None),
+            Ok(())
+        );
+        assert_eq!(
+            poe.get_claim(&content),
+            Some(&Claim {
+                owner: "alice",
+                block_number: 7,
+                timestamp: Some(1_700_000_000_000),
+                deposit: 10,
+                expires_at: None,
+                merkle: false,
+            })
+        );
+
+        // The timestamp is optional, but the block number is always known.
+        runtime.block_number = 8;
+        runtime.timestamp = None;
+        assert_eq!(
+            poe.create_claim(&mut runtime, "bob", hash(b"World"), None),
+            Ok(())
+        );
+        let claim = poe.get_claim(&hash(b"World")).unwrap();
+        assert_eq!((claim.block_number, claim.timestamp), (8, None));
     }
 
     #[test]
     fn cant_claim_existing_claim() {
         let alice = "alice";
         let bob = "bob";
-        let content = "Hello";
+        let content = hash(b"Hello");
+        let mut runtime = runtime();
         let mut poe = super::Pallet::<TestConfig>::new();
 
-        let _ = poe.create_claim(alice, content);
-        let cant_claim = poe.create_claim(bob, content);
+        let _ = poe.create_claim(&mut runtime, alice, content, None);
+        let cant_claim = poe.create_claim(&mut runtime, bob, content, None);
         assert!(cant_claim.is_err());
-        assert_eq!(cant_claim.unwrap_err(), "this content is already claimed");
+        assert_eq!(cant_claim.unwrap_err(), Error::AlreadyClaimed);
     }
 
     #[test]
     fn revoke_claim() {
         let alice = "alice";
         let bob = "bob";
+        let mut runtime = runtime();
         let mut poe = super::Pallet::<TestConfig>::new();
-        let content = "Hello";
+        let content = hash(b"Hello");
 
-        let _ = poe.create_claim(alice, content);
-        let cant_revoke_others_claim = poe.revoke_claim(bob, content);
+        let _ = poe.create_claim(&mut runtime, alice, content, None);
+        let cant_revoke_others_claim = poe.revoke_claim(&mut runtime, bob, content);
 
         assert!(cant_revoke_others_claim.is_err());
+        assert_eq!(cant_revoke_others_claim.unwrap_err(), Error::NotClaimOwner);
+        assert!(poe.revoke_claim(&mut runtime, alice, content).is_ok());
+        assert!(poe.get_claim(&content).is_none());
+        assert_eq!(
+            poe.take_events(),
+            vec![
+                Event::ClaimCreated {
+                    owner: alice,
+                    claim: content
+                },
+                Event::ClaimRevoked {
+                    owner: alice,
+                    claim: content
+                },
+            ]
+        );
+        assert_eq!(
+            poe.revoke_claim(&mut runtime, bob, content).unwrap_err(),
+            Error::ClaimNotFound
+        );
+    }
+
+    #[test]
+    fn transfer_claim() {
+        let mut runtime = TestConfig {
+            block_number: 3,
+            ..runtime()
+        };
+        let mut poe = super::Pallet::<TestConfig>::new();
+        let content = hash(b"Hello");
+
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "alice", content, "bob"),
+            Err(Error::ClaimNotFound)
+        );
+        let _ = poe.create_claim(&mut runtime, "alice", content, None);
+        let _ = poe.take_events();
+
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "bob", content, "charlie"),
+            Err(Error::NotClaimOwner)
+        );
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "alice", content, "alice"),
+            Err(Error::TransferToSelf)
+        );
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "alice", content, "bob"),
+            Ok(())
+        );
+        assert_eq!(
+            poe.get_claim(&content),
+            Some(&Claim {
+                owner: "bob",
+                block_number: 3,
+                timestamp: None,
+                deposit: 10,
+                expires_at: None,
+                merkle: false,
+            })
+        );
+        assert_eq!(
+            poe.take_events(),
+            vec![Event::OwnerChanged {
+                from: "alice",
+                to: "bob",
+                claim: content
+            }]
+        );
+
+        // The deposit moved with the claim.
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 0);
+        assert_eq!(runtime.currency.reserved_balance(&"bob"), 10);
+
+        // Only the new owner can revoke the claim now.
+        assert_eq!(
+            poe.revoke_claim(&mut runtime, "alice", content),
+            Err(Error::NotClaimOwner)
+        );
+        assert_eq!(poe.revoke_claim(&mut runtime, "bob", content), Ok(()));
+    }
+
+    #[test]
+    fn claims_reserve_a_deposit() {
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+        runtime.currency.free.insert("dave", 15);
+
+        assert_eq!(
+            poe.create_claim(&mut runtime, "dave", hash(b"Hello"), None),
+            Ok(())
+        );
+        assert_eq!(runtime.currency.free_balance(&"dave"), 5);
+        assert_eq!(runtime.currency.reserved_balance(&"dave"), 10);
+
+        // Dave can't afford another deposit, nor take over the deposit of another claim.
+        assert_eq!(
+            poe.create_claim(&mut runtime, "dave", hash(b"World"), None),
+            Err(Error::InsufficientBalance)
+        );
+        assert!(poe.get_claim(&hash(b"World")).is_none());
+        let _ = poe.create_claim(&mut runtime, "alice", hash(b"World"), None);
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "alice", hash(b"World"), "dave"),
+            Err(Error::InsufficientBalance)
+        );
+
+        // Revoking the claim returns the deposit.
+        assert_eq!(
+            poe.revoke_claim(&mut runtime, "dave", hash(b"Hello")),
+            Ok(())
+        );
+        assert_eq!(runtime.currency.free_balance(&"dave"), 15);
+        assert_eq!(runtime.currency.reserved_balance(&"dave"), 0);
+
+        // A claim whose deposit was partly slashed can still be revoked, returning what is left.
+        let free = runtime.currency.free_balance(&"alice");
+        runtime.currency.reserved.insert("alice", 4);
+        assert_eq!(
+            poe.revoke_claim(&mut runtime, "alice", hash(b"World")),
+            Ok(())
+        );
+        assert!(poe.get_claim(&hash(b"World")).is_none());
+        assert_eq!(poe.claim_count(&"alice"), 0);
+        assert_eq!(runtime.currency.free_balance(&"alice"), free + 4);
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 0);
+
+        // Such a claim can be transferred too, with the new owner reserving the full deposit.
+        let _ = poe.create_claim(&mut runtime, "alice", hash(b"Again"), None);
+        runtime.currency.reserved.insert("alice", 4);
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "alice", hash(b"Again"), "bob"),
+            Ok(())
+        );
+        assert_eq!(poe.get_claim(&hash(b"Again")).unwrap().owner, "bob");
+        assert_eq!(runtime.currency.free_balance(&"alice"), free - 2);
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 0);
+        assert_eq!(runtime.currency.reserved_balance(&"bob"), 10);
+    }
+
+    #[test]
+    fn expired_claims_can_be_revoked_by_anyone() {
+        let mut runtime = TestConfig {
+            block_number: 5,
+            ..runtime()
+        };
+        let mut poe = super::Pallet::<TestConfig>::new();
+        let content = hash(b"Hello");
+
+        assert_eq!(
+            poe.create_claim(&mut runtime, "alice", content, Some(u32::MAX)),
+            Err(Error::LifetimeOverflow)
+        );
+        // A claim can't be expired from the start, charging its owner for nothing.
+        assert_eq!(
+            poe.create_claim(&mut runtime, "alice", content, Some(0)),
+            Err(Error::InvalidLifetime)
+        );
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 0);
+        assert_eq!(
+            poe.create_claim(&mut runtime, "alice", content, Some(3)),
+            Ok(())
+        );
+        assert_eq!(poe.get_claim(&content).unwrap().expires_at, Some(8));
+
+        runtime.block_number = 7;
+        assert_eq!(
+            poe.revoke_claim(&mut runtime, "bob", content),
+            Err(Error::NotClaimOwner)
+        );
+
+        // Once expired, the claim can't be renewed or transferred, but anyone can revoke it. The
+        // deposit is returned to the owner.
+        runtime.block_number = 8;
+        assert_eq!(
+            poe.renew_claim(&mut runtime, "alice", content, None),
+            Err(Error::ClaimExpired)
+        );
         assert_eq!(
-            cant_revoke_others_claim.unwrap_err(),
-            "this claim is owned by someone else"
+            poe.transfer_claim(&mut runtime, "alice", content, "bob"),
+            Err(Error::ClaimExpired)
         );
-        assert!(poe.revoke_claim(alice, content).is_ok());
+        assert_eq!(poe.revoke_claim(&mut runtime, "bob", content), Ok(()));
         assert!(poe.get_claim(&content).is_none());
+        assert!(poe.expiries.is_empty());
+        assert_eq!(runtime.currency.free_balance(&"alice"), 100);
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 0);
         assert_eq!(
-            poe.revoke_claim(bob, content).unwrap_err(),
-            "claim not found"
+            poe.take_events().last(),
+            Some(&Event::ClaimRevoked {
+                owner: "alice",
+                claim: content
+            })
         );
     }
+
+    #[test]
+    fn renew_claim() {
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+        let content = hash(b"Hello");
+
+        assert_eq!(
+            poe.renew_claim(&mut runtime, "alice", content, None),
+            Err(Error::ClaimNotFound)
+        );
+        let _ = poe.create_claim(&mut runtime, "alice", content, Some(2));
+        let _ = poe.take_events();
+        assert_eq!(
+            poe.renew_claim(&mut runtime, "bob", content, None),
+            Err(Error::NotClaimOwner)
+        );
+
+        runtime.block_number = 1;
+        assert_eq!(
+            poe.renew_claim(&mut runtime, "alice", content, Some(4)),
+            Ok(())
+        );
+        assert_eq!(poe.get_claim(&content).unwrap().expires_at, Some(5));
+        assert_eq!(poe.expiries, BTreeMap::from([(5, vec![content])]));
+        assert_eq!(
+            poe.take_events(),
+            vec![Event::ClaimRenewed {
+                owner: "alice",
+                claim: content,
+                expires_at: Some(5)
+            }]
+        );
+
+        // A claim renewed without a lifetime never expires.
+        assert_eq!(
+            poe.renew_claim(&mut runtime, "alice", content, None),
+            Ok(())
+        );
+        assert_eq!(poe.get_claim(&content).unwrap().expires_at, None);
+        assert!(poe.expiries.is_empty());
+        poe.on_finalize(&mut runtime, u32::MAX);
+        assert!(poe.get_claim(&content).is_some());
+    }
+
+    #[test]
+    fn expired_claims_are_purged() {
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+
+        let _ = poe.create_claim(&mut runtime, "alice", hash(b"Hello"), Some(2));
+        let _ = poe.create_claim(&mut runtime, "bob", hash(b"World"), Some(2));
+        let _ = poe.create_claim(&mut runtime, "charlie", hash(b"!"), Some(3));
+        let _ = poe.take_events();
+
+        poe.on_finalize(&mut runtime, 1);
+        assert!(poe.take_events().is_empty());
+
+        poe.on_finalize(&mut runtime, 2);
+        assert_eq!(
+            poe.take_events(),
+            vec![
+                Event::ClaimExpired {
+                    owner: "alice",
+                    claim: hash(b"Hello")
+                },
+                Event::ClaimExpired {
+                    owner: "bob",
+                    claim: hash(b"World")
+                },
+            ]
+        );
+        assert!(poe.get_claim(&hash(b"Hello")).is_none());
+        assert!(poe.get_claim(&hash(b"World")).is_none());
+        assert!(poe.get_claim(&hash(b"!")).is_some());
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 0);
+        assert_eq!(runtime.currency.free_balance(&"bob"), 100);
+        assert_eq!(runtime.currency.reserved_balance(&"charlie"), 10);
+    }
+
+    #[test]
+    fn claims_of_an_account() {
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+        let mut contents = [hash(b"a"), hash(b"b"), hash(b"c")];
+        for content in contents {
+            let _ = poe.create_claim(&mut runtime, "alice", content, Some(5));
+        }
+        contents.sort();
+        let page = |poe: &super::Pallet<TestConfig>, start_after, limit| {
+            poe.claims_of(&"alice", start_after, limit)
+                .into_iter()
+                .map(|(content, _)| *content)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(poe.claim_count(&"alice"), 3);
+        assert_eq!(page(&poe, None, 10), contents.to_vec());
+        assert_eq!(page(&poe, None, 2), contents[..2].to_vec());
+        assert_eq!(page(&poe, Some(&contents[1]), 2), contents[2..].to_vec());
+        assert!(page(&poe, Some(&contents[2]), 2).is_empty());
+        assert!(poe.claims_of(&"bob", None, 10).is_empty());
+
+        // The index follows the claims when they are transferred, revoked or purged.
+        let _ = poe.transfer_claim(&mut runtime, "alice", contents[0], "bob");
+        let _ = poe.revoke_claim(&mut runtime, "alice", contents[1]);
+        assert_eq!(page(&poe, None, 10), contents[2..].to_vec());
+        assert_eq!(
+            poe.claims_of(&"bob", None, 10),
+            vec![(&contents[0], poe.get_claim(&contents[0]).unwrap())]
+        );
+        poe.on_finalize(&mut runtime, 5);
+        assert_eq!(poe.claim_count(&"alice"), 0);
+        assert_eq!(poe.claim_count(&"bob"), 0);
+        assert!(poe.owned.is_empty());
+    }
+
+    #[test]
+    fn accounts_own_a_limited_number_of_claims() {
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+        for content in [b"a", b"b", b"c"] {
+            assert_eq!(
+                poe.create_claim(&mut runtime, "alice", hash(content), None),
+                Ok(())
+            );
+        }
+
+        assert_eq!(
+            poe.create_claim(&mut runtime, "alice", hash(b"d"), None),
+            Err(Error::TooManyClaims)
+        );
+        let _ = poe.create_claim(&mut runtime, "bob", hash(b"d"), None);
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "bob", hash(b"d"), "alice"),
+            Err(Error::TooManyClaims)
+        );
+
+        // Giving a claim away makes room for another one.
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "alice", hash(b"a"), "bob"),
+            Ok(())
+        );
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, "bob", hash(b"d"), "alice"),
+            Ok(())
+        );
+        assert_eq!(poe.claim_count(&"alice"), 3);
+        assert_eq!(poe.claim_count(&"bob"), 1);
+    }
+
+    #[test]
+    fn create_claims_batch() {
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+        let contents = vec![hash(b"a"), hash(b"b"), hash(b"c")];
+
+        let call = super::Call::<TestConfig>::create_claims_batch {
+            claims: contents.clone(),
+            lifetime: None,
+        };
+        assert_eq!(call.weight(), 60);
+        assert_eq!(poe.dispatch(&mut runtime, "alice", call), Ok(()));
+        for content in &contents {
+            assert_eq!(poe.get_claim(content).unwrap().owner, "alice");
+        }
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 30);
+        assert_eq!(poe.take_events().len(), 3);
+
+        // The batch stops at the first claim which can't be created.
+        assert_eq!(
+            poe.create_claims_batch(&mut runtime, "bob", vec![hash(b"d"), hash(b"a")], None),
+            Err(Error::AlreadyClaimed)
+        );
+    }
+
+    #[test]
+    fn merkle_claims() {
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+        let documents = [b"a", b"b", b"c"].map(|document| hash(document));
+        let root = super::Pallet::<TestConfig>::merkle_root(&documents).unwrap();
+        let proof = super::Pallet::<TestConfig>::merkle_proof(&documents, 2).unwrap();
+
+        assert!(super::Pallet::<TestConfig>::merkle_root(&[]).is_none());
+        assert!(poe.verify_merkle_claim(&documents[2], &proof).is_none());
+        assert_eq!(
+            poe.create_merkle_claim(&mut runtime, "alice", root, None),
+            Ok(())
+        );
+        assert_eq!(runtime.currency.reserved_balance(&"alice"), 10);
+
+        let claim = poe.verify_merkle_claim(&documents[2], &proof).unwrap();
+        assert_eq!((claim.owner, claim.merkle), ("alice", true));
+        assert!(poe.verify_merkle_claim(&documents[1], &proof).is_none());
+        assert!(poe.get_claim(&documents[2]).is_none());
+
+        // A plain claim on the same root proves nothing about the documents.
+        let _ = poe.revoke_claim(&mut runtime, "alice", root);
+        let _ = poe.create_claim(&mut runtime, "alice", root, None);
+        assert!(poe.verify_merkle_claim(&documents[2], &proof).is_none());
+    }
+
+    #[test]
+    fn verify_file() {
+        // The process id keeps concurrent test runs from writing to the same file.
+        let path = std::env::temp_dir().join(format!(
+            "proof_of_existence_verify_file_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"Hello").unwrap();
+        let mut runtime = runtime();
+        let mut poe = super::Pallet::<TestConfig>::new();
+
+        assert_eq!(
+            super::Pallet::<TestConfig>::hash_file(&path).unwrap(),
+            hash(b"Hello")
+        );
+        assert_eq!(poe.verify_file(&path).unwrap(), None);
+        let _ = poe.create_claim(&mut runtime, "alice", hash(b"Hello"), None);
+        assert_eq!(
+            poe.verify_file(&path).unwrap().map(|claim| claim.owner),
+            Some("alice")
+        );
+
+        // Any change to the file breaks the proof.
+        std::fs::write(&path, b"Hello!").unwrap();
+        assert_eq!(poe.verify_file(&path).unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+        assert!(poe.verify_file(&path).is_err());
+    }
 }
@@ -1,4 +1,17 @@
+use crate::codec::{Decode, Encode, Error};
+use crate::crypto::{Pair, Public, Signature};
+use sha2::{Digest, Sha256};
+
+/// The output of our hash function.
+pub type Hash = [u8; 32];
+
+/// Hash `data` with SHA-256. This is the hash function used for block headers and storage roots.
+pub fn hash(data: &[u8]) -> Hash {
+    Sha256::digest(data).into()
+}
+
 /// The most primitive representation of a Blockchain block.
+#[derive(Debug, Clone)]
 pub struct Block<Header, Extrinsic> {
     /// The block header contains metadata about the block.
     pub header: Header,
@@ -6,27 +19,181 @@ pub struct Block<Header, Extrinsic> {
     pub extrinsics: Vec<Extrinsic>,
 }
 
-/// We are using an extremely simplified header which only contains the current block number.
-/// On a real blockchain, you would expect to also find:
-/// - parent block hash
-/// - state root
-/// - extrinsics root
-/// - etc...
+/// A block is encoded as its header followed by its extrinsics, so it can be stored or sent to
+/// other nodes.
+impl<Header: Encode, Extrinsic: Encode> Encode for Block<Header, Extrinsic> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.header.encode_to(dest);
+        self.extrinsics.encode_to(dest);
+    }
+}
+
+impl<Header: Decode, Extrinsic: Decode> Decode for Block<Header, Extrinsic> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Block {
+            header: Decode::decode_from(input)?,
+            extrinsics: Decode::decode_from(input)?,
+        })
+    }
+}
+
+/// We are using a simplified header, which links the block to its parent and commits to its
+/// content. On a real blockchain, you would expect to also find a digest with extra information,
+/// like the block author.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Header<BlockNumber> {
+    /// The hash of the header of the parent block.
+    pub parent_hash: Hash,
     pub block_number: BlockNumber,
+    /// The hash of the encoded extrinsics of this block. See `extrinsics_root`.
+    pub extrinsics_root: Hash,
+    /// The hash of the encoded state of the runtime after executing this block.
+    pub state_root: Hash,
+}
+
+impl<BlockNumber: Encode> Encode for Header<BlockNumber> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.parent_hash.encode_to(dest);
+        self.block_number.encode_to(dest);
+        self.extrinsics_root.encode_to(dest);
+        self.state_root.encode_to(dest);
+    }
+}
+
+impl<BlockNumber: Decode> Decode for Header<BlockNumber> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Header {
+            parent_hash: Decode::decode_from(input)?,
+            block_number: Decode::decode_from(input)?,
+            extrinsics_root: Decode::decode_from(input)?,
+            state_root: Decode::decode_from(input)?,
+        })
+    }
+}
+
+impl<BlockNumber: Encode> Header<BlockNumber> {
+    /// The hash of this header, which identifies the block.
+    pub fn hash(&self) -> Hash {
+        hash(&self.encode())
+    }
+}
+
+/// Compute the extrinsics root of a block from its extrinsics.
+pub fn extrinsics_root<Extrinsic: Encode>(extrinsics: &[Extrinsic]) -> Hash {
+    hash(&extrinsics.encode())
 }
 
 /// This is an "extrinsic": literally an external message from outside of the blockchain.
-/// This simplified version of an extrinsic tells us who is making the call, and which call they are
-/// making.
-pub struct Extrinsic<Caller, Call> {
-    pub caller: Caller,
+/// This simplified version of an extrinsic tells us who signed it, and which call they are
+/// making. The account making the call is derived from the public key of the signer.
+#[derive(Debug, Clone)]
+pub struct Extrinsic<Nonce, Call> {
+    /// The public key of the signer.
+    pub signer: Public,
+    /// The signature of the signer over the signing payload, see `Extrinsic::signing_payload`.
+    pub signature: Signature,
+    /// The nonce of the signer, which must match the nonce of the account in the System Pallet.
+    /// Since the nonce is incremented by every extrinsic, the same extrinsic can't be replayed.
+    pub nonce: Nonce,
     pub call: Call,
 }
 
+impl<Nonce: Encode, Call: Encode> Extrinsic<Nonce, Call> {
+    /// Create an extrinsic signed by `pair` for the chain with the given `genesis_hash`.
+    pub fn new_signed(pair: &Pair, nonce: Nonce, call: Call, genesis_hash: Hash) -> Self {
+        let signature = pair.sign(&Self::signing_payload(&call, &nonce, &genesis_hash));
+        Extrinsic {
+            signer: pair.public(),
+            signature,
+            nonce,
+            call,
+        }
+    }
+
+    /// The bytes which are signed: the encoded call, nonce and genesis hash. Including the genesis
+    /// hash means an extrinsic signed for one chain is not valid on any other chain.
+    pub fn signing_payload(call: &Call, nonce: &Nonce, genesis_hash: &Hash) -> Vec<u8> {
+        let mut payload = call.encode();
+        nonce.encode_to(&mut payload);
+        genesis_hash.encode_to(&mut payload);
+        payload
+    }
+
+    /// Check the signature of this extrinsic for the chain with the given `genesis_hash`.
+    pub fn verify(&self, genesis_hash: &Hash) -> bool {
+        let payload = Self::signing_payload(&self.call, &self.nonce, genesis_hash);
+        self.signature.verify(&payload, &self.signer)
+    }
+}
+
+impl<Nonce: Encode, Call: Encode> Encode for Extrinsic<Nonce, Call> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.signer.encode_to(dest);
+        self.signature.encode_to(dest);
+        self.nonce.encode_to(dest);
+        self.call.encode_to(dest);
+    }
+}
+
+impl<Nonce: Decode, Call: Decode> Decode for Extrinsic<Nonce, Call> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Extrinsic {
+            signer: Decode::decode_from(input)?,
+            signature: Decode::decode_from(input)?,
+            nonce: Decode::decode_from(input)?,
+            call: Decode::decode_from(input)?,
+        })
+    }
+}
+
+/// A measure of the computation needed to execute a call. Every call declares its weight with
+/// `#[weight(...)]`, see `macros::call`.
+pub type Weight = u64;
+
+/// The reason a call could not be dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchError {
+    /// An error declared by a pallet in its `Error` enum.
+    Module(ModuleError),
+    /// Any other error, described by a static message.
+    Other(&'static str),
+}
+
+impl DispatchError {
+    /// Build the error raised by `error` from the pallet at `index` in the runtime.
+    pub fn module<E: PalletError>(index: u8, error: E) -> Self {
+        DispatchError::Module(ModuleError {
+            index,
+            error: error.index(),
+            message: error.as_str(),
+        })
+    }
+}
+
+/// An error raised by a pallet, which can be matched on without comparing strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleError {
+    /// The index of the pallet in the runtime, where the system pallet has index 0.
+    pub index: u8,
+    /// The index of the variant in the `Error` enum of the pallet.
+    pub error: u8,
+    /// A human readable description of the error.
+    pub message: &'static str,
+}
+
+/// Every pallet declares an `Error` enum listing the ways its calls can fail. This trait lets the
+/// runtime turn them into a `DispatchError::Module`.
+pub trait PalletError {
+    /// The index of this variant in the `Error` enum of the pallet.
+    fn index(&self) -> u8;
+    /// A human readable description of the error.
+    fn as_str(&self) -> &'static str;
+}
+
 /// The Result type for our runtime. When everything completes successfully, we return `Ok(())`,
-/// otherwise return a static error message.
-pub type DispatchResult = Result<(), &'static str>;
+/// otherwise return an error. Pallets use their own `Error` enum, while the runtime uses
+/// `DispatchError`.
+pub type DispatchResult<E = DispatchError> = Result<(), E>;
 
 /// A trait which allows us to dispatch an incoming extrinsic to the appropriate state transition
 /// function call.
@@ -35,8 +202,146 @@ pub trait Dispatch {
     type Caller;
     /// The state transition function call the caller is trying to access.
     type Call;
+    /// The error returned when the call fails.
+    type Error;
 
     /// This function takes a `caller` and the `call` they want to make, and returns a `Result`
     /// based on the outcome of that function call.
-    fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
+    fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult<Self::Error>;
+}
+
+/// A currency which pallets can depend on without being coupled to a particular pallet. A pallet
+/// declares `type Currency: Currency<Self::AccountId>` in its `Config`, the runtime sets it to the
+/// Balances Pallet, and unit tests can use a simple mock instead.
+///
+/// Failures are reported with the `Error` of the currency, so a pallet using it can match on them
+/// without comparing strings.
+pub trait Currency<AccountId> {
+    /// The type used to represent an amount of this currency.
+    type Balance: Copy + core::fmt::Debug + PartialOrd + Encode;
+    /// The reasons an operation on this currency can fail.
+    type Error: PalletError + core::fmt::Debug;
+
+    /// The balance of `who` which can be transferred.
+    fn free_balance(&self, who: &AccountId) -> Self::Balance;
+    /// The balance of `who` which is reserved, and can't be transferred.
+    fn reserved_balance(&self, who: &AccountId) -> Self::Balance;
+    /// Transfer `amount` of free balance from `from` to `to`.
+    fn transfer(
+        &mut self,
+        from: &AccountId,
+        to: &AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult<Self::Error>;
+    /// Remove `amount` from the free balance of `who`, decreasing the total issuance.
+    fn withdraw(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult<Self::Error>;
+    /// Add `amount` to the free balance of `who`, increasing the total issuance.
+    fn deposit(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult<Self::Error>;
+    /// Move `amount` from the free balance of `who` to its reserved balance.
+    fn reserve(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult<Self::Error>;
+    /// Move `amount` from the reserved balance of `who` back to its free balance.
+    fn unreserve(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult<Self::Error>;
+}
+
+/// Logic which a pallet runs in every block, whatever the extrinsics of the block. Every pallet
+/// implements this trait, and `#[macros::runtime]` calls the hooks of the pallets in the order
+/// they are declared in the runtime. Both hooks do nothing by default.
+///
+/// Like calls, hooks are given the rest of the `Runtime`, along with the number of the block being
+/// executed.
+pub trait Hooks<Runtime, BlockNumber> {
+    /// Called at the start of every block, before its extrinsics are applied.
+    fn on_initialize(&mut self, _runtime: &mut Runtime, _block_number: BlockNumber) {}
+    /// Called at the end of every block, after its extrinsics are applied.
+    fn on_finalize(&mut self, _runtime: &mut Runtime, _block_number: BlockNumber) {}
+}
+
+/// The initial state of a pallet. Every pallet declares a `GenesisConfig` implementing this trait,
+/// which `#[macros::runtime]` aggregates into a `RuntimeGenesisConfig` to build the genesis state
+/// of the runtime with `Runtime::from_genesis`.
+pub trait BuildGenesisConfig<Pallet, Runtime> {
+    /// Write this initial state into a new `pallet`. Like calls, this is given the rest of the
+    /// `Runtime`, whose pallets declared before this one are already built.
+    fn build(&self, pallet: &mut Pallet, runtime: &mut Runtime) -> DispatchResult;
+}
+
+/// Execute `f` inside a storage transaction on `state`.
+///
+/// Our storage lives in plain Rust structs, so a transaction simply keeps a copy of `state` and
+/// restores it if `f` returns an error, leaving no partial writes behind. Transactions can be
+/// nested: a call dispatching a batch of other calls can run each one in its own transaction, and
+/// still be rolled back as a whole by the outer one.
+///
+/// The copy makes every transaction cost as much as cloning the whole of `state`, so a block of
+/// `n` extrinsics clones the state of the runtime `n` times. Data which can't be touched by `f` and
+/// may grow large, like the events of the block, should be moved out of `state` beforehand.
+pub fn with_transaction<S, R, E>(
+    state: &mut S,
+    f: impl FnOnce(&mut S) -> Result<R, E>,
+) -> Result<R, E>
+where
+    S: Clone,
+{
+    let snapshot = state.clone();
+    let result = f(state);
+    if result.is_err() {
+        *state = snapshot;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::with_transaction;
+
+    #[test]
+    fn failed_transaction_is_rolled_back() {
+        let mut state = vec![1];
+
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            state.push(2);
+            Err("failed")
+        });
+        assert_eq!(result, Err("failed"));
+        assert_eq!(state, vec![1]);
+
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            state.push(2);
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(state, vec![1, 2]);
+    }
+
+    #[test]
+    fn nested_transactions() {
+        let mut state = vec![1];
+
+        // Only the failing inner transaction is rolled back.
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            state.push(2);
+            let inner: Result<(), &str> = with_transaction(state, |state| {
+                state.push(3);
+                Err("inner failed")
+            });
+            assert!(inner.is_err());
+            with_transaction(state, |state| {
+                state.push(4);
+                Ok(())
+            })
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(state, vec![1, 2, 4]);
+
+        // A failing outer transaction rolls back the committed inner ones too.
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            with_transaction(state, |state| {
+                state.push(5);
+                Ok(())
+            })?;
+            Err("outer failed")
+        });
+        assert_eq!(result, Err("outer failed"));
+        assert_eq!(state, vec![1, 2, 4]);
+    }
 }
@@ -1,26 +1,87 @@
-use num::traits::{One, Zero};
+use crate::codec::Encode;
+use crate::support::{BuildGenesisConfig, DispatchError, DispatchResult, Hash, Weight};
+use num::traits::{CheckedAdd, One, Zero};
 use std::collections::BTreeMap;
+use std::fmt::Debug;
 
 pub trait Config {
-    type AccountId: Ord + Clone;
-    type BlockNumber: Zero + One + Copy; // AddAssign
-    type Nonce: Zero + Copy + One;
+    type AccountId: Debug + Ord + Clone + Encode;
+    type BlockNumber: Debug + Zero + One + CheckedAdd + Copy + Ord + Encode;
+    type Nonce: Zero + Copy + One + PartialEq + Encode;
+    /// The aggregated event type of the runtime, generated as `RuntimeEvent` by
+    /// `#[macros::runtime]`.
+    type RuntimeEvent: Debug + Clone;
+
+    /// The default maximum total weight of the calls in a block, which the genesis config of a
+    /// chain can override.
+    const MAX_BLOCK_WEIGHT: Weight;
+
+    /// Charge `who` the fee of an extrinsic of `len` bytes dispatching a call of the given
+    /// `weight`, before the call is dispatched. An extrinsic whose caller can't pay the fee is
+    /// rejected. By default, extrinsics are free.
+    fn charge_transaction_fee(
+        &mut self,
+        _who: &Self::AccountId,
+        _len: u64,
+        _weight: Weight,
+    ) -> DispatchResult {
+        Ok(())
+    }
+}
+
+/// The events emitted by the System Pallet itself, once for every extrinsic in a block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// An extrinsic was dispatched successfully.
+    ExtrinsicSuccess,
+    /// An extrinsic failed with the given error.
+    ExtrinsicFailed { error: DispatchError },
+}
+
+/// The phase of block execution in which an event was emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Before the extrinsics of the block were applied.
+    Initialization,
+    /// While applying the extrinsic with the given index.
+    ApplyExtrinsic(u32),
+    /// After all the extrinsics of the block were applied.
+    Finalization,
+}
+
+/// An event deposited during block execution, together with the phase in which it was emitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord<Event> {
+    pub phase: Phase,
+    pub event: Event,
 }
 
 /// module for blockchain metadata
 /// This is the System Pallet
 /// It handles low level state needed for your blockchain
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
     block_number: T::BlockNumber,
+    /// The hash of the genesis block, which identifies the chain.
+    genesis_hash: Hash,
+    /// The hash of the last executed block, which must be the parent of the next block.
+    parent_hash: Hash,
     nonce: BTreeMap<T::AccountId, T::Nonce>,
+    /// The maximum total weight of the calls in a block.
+    max_block_weight: Weight,
+    /// The events deposited in the current block. Cleared at the start of every block.
+    events: Vec<EventRecord<T::RuntimeEvent>>,
 }
 
 impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
         Pallet {
             block_number: T::BlockNumber::zero(),
+            genesis_hash: Hash::default(),
+            parent_hash: Hash::default(),
             nonce: BTreeMap::new(),
+            max_block_weight: T::MAX_BLOCK_WEIGHT,
+            events: Vec::new(),
         }
     }
 
@@ -32,15 +93,100 @@ impl<T: Config> Pallet<T> {
         self.block_number = self.block_number + T::BlockNumber::one();
     }
 
+    pub fn genesis_hash(&self) -> Hash {
+        self.genesis_hash
+    }
+
+    /// Record the hash of the genesis block, which is also the parent of the first block.
+    pub fn set_genesis_hash(&mut self, hash: Hash) {
+        self.genesis_hash = hash;
+        self.parent_hash = hash;
+    }
+
+    pub fn parent_hash(&self) -> Hash {
+        self.parent_hash
+    }
+
+    /// Record the hash of the block which was just executed.
+    pub fn set_parent_hash(&mut self, hash: Hash) {
+        self.parent_hash = hash;
+    }
+
+    /// The maximum total weight of the calls in a block. A heavier block is rejected.
+    pub fn max_block_weight(&self) -> Weight {
+        self.max_block_weight
+    }
+
+    /// The nonce of `account`, which is the nonce its next extrinsic must carry.
+    pub fn nonce(&self, account: &T::AccountId) -> T::Nonce {
+        *self.nonce.get(account).unwrap_or(&T::Nonce::zero())
+    }
+
     pub fn inc_nonce(&mut self, account: &T::AccountId) {
-        let nonce = *self.nonce.get(account).unwrap_or(&T::Nonce::zero()) + T::Nonce::one();
+        let nonce = self.nonce(account) + T::Nonce::one();
         self.nonce.insert(account.clone(), nonce);
     }
+
+    /// Record an `event` emitted during `phase` of the current block.
+    pub fn deposit_event(&mut self, phase: Phase, event: T::RuntimeEvent) {
+        self.events.push(EventRecord { phase, event });
+    }
+
+    /// All the events deposited in the current block, in the order they were emitted.
+    pub fn events(&self) -> &[EventRecord<T::RuntimeEvent>] {
+        &self.events
+    }
+
+    /// Clear the events of the previous block.
+    pub fn reset_events(&mut self) {
+        self.events.clear();
+    }
+
+    /// Move the events of the current block out of the pallet, so a storage transaction doesn't
+    /// copy them. They are put back with `restore_events`.
+    pub fn take_events(&mut self) -> Vec<EventRecord<T::RuntimeEvent>> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Put back the events moved out by `take_events`, before any event deposited since.
+    pub fn restore_events(&mut self, mut events: Vec<EventRecord<T::RuntimeEvent>>) {
+        events.append(&mut self.events);
+        self.events = events;
+    }
+}
+
+/// The state of the System Pallet, as committed to by the state root. Events are not part of it,
+/// since they only describe the last block.
+impl<T: Config> Encode for Pallet<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.block_number.encode_to(dest);
+        self.genesis_hash.encode_to(dest);
+        self.parent_hash.encode_to(dest);
+        self.nonce.encode_to(dest);
+        self.max_block_weight.encode_to(dest);
+    }
+}
+
+/// The parameters of the System Pallet for a chain.
+#[derive(Debug, Clone, Default)]
+pub struct GenesisConfig {
+    /// The maximum total weight of the calls in a block, or `None` for
+    /// `Config::MAX_BLOCK_WEIGHT`.
+    pub max_block_weight: Option<Weight>,
+}
+
+impl<T: Config, Runtime> BuildGenesisConfig<Pallet<T>, Runtime> for GenesisConfig {
+    fn build(&self, pallet: &mut Pallet<T>, _runtime: &mut Runtime) -> DispatchResult {
+        if let Some(max_block_weight) = self.max_block_weight {
+            pallet.max_block_weight = max_block_weight;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::system::Pallet;
+    use crate::system::{Pallet, Phase};
 
     struct TestConfig;
 
@@ -48,6 +194,9 @@ mod test {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = &'static str;
+
+        const MAX_BLOCK_WEIGHT: u64 = 100;
     }
 
     #[test]
@@ -56,11 +205,50 @@ mod test {
 
         assert_eq!(system.block_number, 0);
         assert_eq!(system.nonce.get("daniel"), None);
+        assert_eq!(system.nonce(&"daniel".to_string()), 0);
 
         system.inc_block_number();
         assert_eq!(system.block_number(), 1);
 
         system.inc_nonce(&"daniel".to_string());
         assert_eq!(system.nonce.get("daniel").unwrap(), &1);
+        assert_eq!(system.nonce(&"daniel".to_string()), 1);
+    }
+
+    #[test]
+    fn deposit_and_reset_events() {
+        let mut system = Pallet::<TestConfig>::new();
+
+        system.deposit_event(Phase::ApplyExtrinsic(0), "first");
+        system.deposit_event(Phase::Finalization, "second");
+        assert_eq!(
+            system.events(),
+            &[
+                super::EventRecord {
+                    phase: Phase::ApplyExtrinsic(0),
+                    event: "first"
+                },
+                super::EventRecord {
+                    phase: Phase::Finalization,
+                    event: "second"
+                },
+            ]
+        );
+
+        let events = system.take_events();
+        assert!(system.events().is_empty());
+        system.deposit_event(Phase::Finalization, "third");
+        system.restore_events(events);
+        assert_eq!(
+            system
+                .events()
+                .iter()
+                .map(|record| record.event)
+                .collect::<Vec<_>>(),
+            ["first", "second", "third"]
+        );
+
+        system.reset_events();
+        assert!(system.events().is_empty());
     }
 }